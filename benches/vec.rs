@@ -6,7 +6,7 @@ fn benchmark_pure(c: &mut Criterion) {
     let x = (0..20).map(|x| x as f32).collect::<Vec<_>>();
     let y = (0..20).map(f64::from).collect::<Vec<_>>();
 
-    c.bench_function("pure single", |b| b.iter(|| (black_box(x.clone()))));
+    c.bench_function("pure single", |b| b.iter(|| black_box(x.clone())));
     c.bench_function("pure double", |b| {
         b.iter(|| (black_box(x.clone()), black_box(y.clone())))
     });
@@ -52,7 +52,7 @@ fn benchmark_map(c: &mut Criterion) {
         }
     }
 
-    let x = (0..20).map(|x| x).collect::<Vec<_>>();
+    let x = (0..20).collect::<Vec<_>>();
 
     c.bench_function("map", |b| b.iter(|| black_box(x.clone().map(fib))));
     c.bench_function("map macro", |b| {
@@ -67,5 +67,53 @@ fn benchmark_map(c: &mut Criterion) {
     });
 }
 
-criterion_group! { vec_utils, benchmark_pure, benchmark_map, benchmark_zip }
+fn benchmark_map_copy(c: &mut Criterion) {
+    #[derive(Clone, Copy)]
+    struct Meters(f32);
+
+    let x = (0..4096).map(|x| x as f32).collect::<Vec<_>>();
+
+    c.bench_function("map copy newtype", |b| {
+        b.iter(|| black_box(x.clone().map(Meters).map(|Meters(x)| x)))
+    });
+    c.bench_function("map copy scale", |b| {
+        b.iter(|| black_box(x.clone().map(|x| x * 2.0)))
+    });
+    c.bench_function("map copy iter", |b| {
+        b.iter(|| black_box(x.clone().into_iter().map(|x| x * 2.0).collect::<Vec<_>>()))
+    });
+}
+
+fn benchmark_map_same(c: &mut Criterion) {
+    fn bump(mut x: [u64; 16]) -> [u64; 16] {
+        x.iter_mut()
+            .for_each(|x| *x = x.wrapping_mul(3).wrapping_add(1));
+        x
+    }
+
+    let x = (0..1024).map(|x| [x; 16]).collect::<Vec<_>>();
+
+    c.bench_function("map same type", |b| {
+        b.iter(|| black_box(x.clone().map(bump)))
+    });
+    c.bench_function("map same type in place", |b| {
+        b.iter(|| {
+            let mut x = x.clone();
+            x.map_in_place(|x| *x = bump(*x));
+            black_box(x)
+        })
+    });
+    c.bench_function("map same type iter", |b| {
+        b.iter(|| black_box(x.clone().into_iter().map(bump).collect::<Vec<_>>()))
+    });
+}
+
+criterion_group! {
+    vec_utils,
+    benchmark_pure,
+    benchmark_map,
+    benchmark_map_copy,
+    benchmark_map_same,
+    benchmark_zip
+}
 criterion_main! { vec_utils }
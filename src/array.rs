@@ -0,0 +1,119 @@
+use core::alloc::Layout;
+use core::mem::{ManuallyDrop, MaybeUninit};
+
+use super::{r#try, Try};
+
+/// Extension methods for `[T; N]`
+///
+/// These are named `map_array` and `try_map_array`, because the inherent
+/// `<[T; N]>::map` would always be picked over a trait method named `map`
+pub trait ArrayExt<const N: usize>: Sized {
+    /// The type that the `[T; N]` stores
+    type T;
+
+    /// Map an array to another array, will reuse the storage of the array if the
+    /// allocation layouts of the two types match, i.e. if
+    /// `std::alloc::Layout::<T>::new() == std::alloc::Layout::<U>::new()`
+    /// then the values will be mapped in place
+    fn map_array<U, F: FnMut(Self::T) -> U>(self, mut f: F) -> [U; N] {
+        use core::convert::Infallible;
+
+        match self.try_map_array(move |x| Ok::<_, Infallible>(f(x))) {
+            Ok(x) => x,
+            Err(x) => match x {},
+        }
+    }
+
+    /// Map an array to another array, will reuse the storage of the array if the
+    /// allocation layouts of the two types match, i.e. if
+    /// `std::alloc::Layout::<T>::new() == std::alloc::Layout::<U>::new()`
+    /// then the values will be mapped in place
+    ///
+    /// The mapping function can be fallible, and on early return, it will drop all previous values,
+    /// and the rest of the input array. The error will be returned as a `Result`
+    fn try_map_array<U, R: Try<Ok = U>, F: FnMut(Self::T) -> R>(
+        self,
+        f: F,
+    ) -> Result<[U; N], R::Error>;
+}
+
+impl<T, const N: usize> ArrayExt<N> for [T; N] {
+    type T = T;
+
+    fn try_map_array<U, R: Try<Ok = U>, F: FnMut(Self::T) -> R>(
+        self,
+        mut f: F,
+    ) -> Result<[U; N], R::Error> {
+        let mut iter = ArrayMapIter::<T, U, N> {
+            input: MaybeUninit::new(self),
+            output: MaybeUninit::uninit(),
+            init_len: 0,
+        };
+
+        // does a pointer walk, easy for LLVM to optimize
+        while iter.init_len < N {
+            unsafe {
+                let (input, output) = iter.ptrs();
+                let value = r#try!(f(input.add(iter.init_len).read()));
+
+                output.add(iter.init_len).write(value);
+                iter.init_len += 1;
+            }
+        }
+
+        let mut iter = ManuallyDrop::new(iter);
+
+        unsafe { Ok(iter.ptrs().1.cast::<[U; N]>().read()) }
+    }
+}
+
+struct ArrayMapIter<T, U, const N: usize> {
+    // this is `MaybeUninit` because the output may be written over it,
+    // so it won't always hold valid `T`s
+    input: MaybeUninit<[T; N]>,
+
+    // this is only used if the layouts of `T` and `U` don't match,
+    // otherwise the output is written over the input
+    output: MaybeUninit<[U; N]>,
+
+    init_len: usize,
+}
+
+impl<T, U, const N: usize> ArrayMapIter<T, U, N> {
+    // get pointers to the start of the input and output, these are derived together
+    // so that they can both be used when the output is written over the input
+    fn ptrs(&mut self) -> (*mut T, *mut U) {
+        let input = core::ptr::addr_of_mut!(self.input) as *mut T;
+
+        if Layout::new::<T>() == Layout::new::<U>() {
+            (input, input as *mut U)
+        } else {
+            (input, core::ptr::addr_of_mut!(self.output) as *mut U)
+        }
+    }
+}
+
+impl<T, U, const N: usize> Drop for ArrayMapIter<T, U, N> {
+    fn drop(&mut self) {
+        let init_len = self.init_len;
+        let (input, output) = self.ptrs();
+
+        // this is only dropped from inside the loop in `try_map_array`,
+        // so there is always at least one element that was just read
+        debug_assert!(init_len < N);
+
+        unsafe {
+            // destroy the initialized output
+            defer! {
+                core::ptr::drop_in_place(core::ptr::slice_from_raw_parts_mut(output, init_len));
+            }
+
+            // offset by 1 because the value at `init_len` was
+            // just read from, dropping that would lead to a double free
+            core::ptr::drop_in_place(core::ptr::slice_from_raw_parts_mut(
+                input.add(init_len + 1),
+                N - init_len - 1,
+            ));
+        }
+    }
+}
@@ -1,6 +1,10 @@
-use std::alloc::Layout;
-use std::mem::ManuallyDrop;
-use std::ptr::NonNull;
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::alloc::Layout;
+use core::mem::ManuallyDrop;
+use core::ptr::NonNull;
+
+use super::{Try, VecExt};
 
 /// Extension methods for `Box<T>`
 pub trait BoxExt: Sized {
@@ -55,6 +59,85 @@ impl<T: ?Sized> BoxExt for Box<T> {
     }
 }
 
+/// Extension methods for `Box<[T]>`
+///
+/// A boxed slice has no spare capacity, so the allocation will only be reused
+/// without reallocating if the layouts of the input and output types match exactly
+pub trait BoxSliceExt: Sized {
+    /// The type that the `Box<[T]>` stores
+    type T;
+
+    /// Map a boxed slice to another boxed slice, will try and reuse the allocation if the
+    /// allocation layouts of the two types match, i.e. if
+    /// `std::alloc::Layout::<T>::new() == std::alloc::Layout::<U>::new()`
+    /// then the allocation will be reused
+    fn map<U, F: FnMut(Self::T) -> U>(self, mut f: F) -> Box<[U]> {
+        use core::convert::Infallible;
+
+        match self.try_map(move |x| Ok::<_, Infallible>(f(x))) {
+            Ok(x) => x,
+            Err(x) => match x {},
+        }
+    }
+
+    /// Map a boxed slice to another boxed slice, will try and reuse the allocation if the
+    /// allocation layouts of the two types match, i.e. if
+    /// `std::alloc::Layout::<T>::new() == std::alloc::Layout::<U>::new()`
+    /// then the allocation will be reused
+    ///
+    /// The mapping function can be fallible, and on early return, it will drop all previous values,
+    /// and the rest of the input slice. The error will be returned as a `Result`
+    fn try_map<U, R: Try<Ok = U>, F: FnMut(Self::T) -> R>(self, f: F)
+        -> Result<Box<[U]>, R::Error>;
+
+    /// Zip a boxed slice to another boxed slice and combine them, the result will be returned,
+    /// the allocation will be reused if possible, in the same way as
+    /// [`VecExt::zip_with`](trait.VecExt.html#method.zip_with)
+    fn zip_with<U, V, F: FnMut(Self::T, U) -> V>(self, other: Box<[U]>, mut f: F) -> Box<[V]> {
+        use core::convert::Infallible;
+
+        match self.try_zip_with(other, move |x, y| Ok::<_, Infallible>(f(x, y))) {
+            Ok(x) => x,
+            Err(x) => match x {},
+        }
+    }
+
+    /// Zip a boxed slice to another boxed slice and combine them, the result will be returned,
+    /// the allocation will be reused if possible, in the same way as
+    /// [`VecExt::zip_with`](trait.VecExt.html#method.zip_with)
+    ///
+    /// The mapping function can be fallible, and on early return, it will drop all previous values,
+    /// and the rest of the input slices. The error will be returned as a `Result`
+    fn try_zip_with<U, V, R: Try<Ok = V>, F: FnMut(Self::T, U) -> R>(
+        self,
+        other: Box<[U]>,
+        f: F,
+    ) -> Result<Box<[V]>, R::Error>;
+}
+
+impl<T> BoxSliceExt for Box<[T]> {
+    type T = T;
+
+    fn try_map<U, R: Try<Ok = U>, F: FnMut(Self::T) -> R>(
+        self,
+        f: F,
+    ) -> Result<Box<[U]>, R::Error> {
+        // converting between `Box<[T]>` and `Vec<T>` is free as long as
+        // there is no spare capacity
+        Vec::from(self).try_map(f).map(Vec::into_boxed_slice)
+    }
+
+    fn try_zip_with<U, V, R: Try<Ok = V>, F: FnMut(Self::T, U) -> R>(
+        self,
+        other: Box<[U]>,
+        f: F,
+    ) -> Result<Box<[V]>, R::Error> {
+        Vec::from(self)
+            .try_zip_with(Vec::from(other), f)
+            .map(Vec::into_boxed_slice)
+    }
+}
+
 /// An uninitialized piece of memory
 pub struct UninitBox {
     ptr: NonNull<u8>,
@@ -83,10 +166,10 @@ impl UninitBox {
                 ptr: unsafe { NonNull::new_unchecked(layout.align() as *mut u8) },
             }
         } else {
-            let ptr = unsafe { std::alloc::alloc(layout) };
+            let ptr = unsafe { alloc::alloc::alloc(layout) };
 
             if ptr.is_null() {
-                std::alloc::handle_alloc_error(layout)
+                alloc::alloc::handle_alloc_error(layout)
             } else {
                 unsafe {
                     UninitBox {
@@ -165,6 +248,6 @@ impl UninitBox {
 
 impl Drop for UninitBox {
     fn drop(&mut self) {
-        unsafe { std::alloc::dealloc(self.ptr.as_ptr(), self.layout) }
+        unsafe { alloc::alloc::dealloc(self.ptr.as_ptr(), self.layout) }
     }
 }
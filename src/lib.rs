@@ -1,3 +1,6 @@
+#![no_std]
+#![cfg_attr(feature = "allocator_api", feature(allocator_api))]
+#![cfg_attr(feature = "portable_simd", feature(portable_simd))]
 #![forbid(missing_docs)]
 
 /*!
@@ -55,6 +58,32 @@ fn add(a: Vec<i32>, b: i32) -> Vec<i32> {
 }
 ```
 
+If you need a real closure, the `VecZip` trait does the same zipping as a method on tuples of up to 8 vectors, and passes the elements to the closure as a tuple.
+
+```rust
+use vec_utils::VecZip;
+
+fn sum_3(a: Vec<i32>, b: Vec<i32>, c: Vec<i32>, f: impl Fn(i32) -> i32) -> Vec<i32> {
+    (a, b, c).zip_with(|(a, b, c)| f(a + b + c))
+}
+```
+
+The fallible variants (`try_map`, `try_zip_with`, and `try_zip_with!`) are built on this crate's own `Try` trait, which is implemented for `Result<T, E>` and `Option<T>` (and the `Poll` equivalents), so everything works on stable Rust without `#![feature(try_trait)]`.
+
+This crate is `no_std` compatible, it only needs `alloc`. To use it without the standard library, disable the default `std` feature.
+
+On nightly, the `allocator_api` feature adds `VecInExt`, which maps and zips vectors with custom allocators, and keeps the output in the same allocator as the input.
+
+On nightly, the `portable_simd` feature adds `VecExt::map_simd`, which maps a vector a few lanes at a time with `core::simd`, so that the mapping function can be written with SIMD types.
+
+The `bumpalo` feature adds `VecExt::map_in_bump`, which maps a vector into a `bumpalo` arena instead of the global allocator.
+
+The `smallvec` feature adds `SmallVecExt`, which maps and zips `SmallVec`s. Spilled vectors reuse their heap allocation in the same way as `Vec`, and inline vectors stay inline without allocating.
+
+The `tinyvec` feature adds `ArrayVecExt` and `TinyVecExt`, which map and zip `tinyvec`'s `ArrayVec`s and `TinyVec`s. Heap vectors reuse their allocation in the same way as `Vec`, and inline vectors are mapped in place when the layouts match.
+
+The `disable-reuse` feature turns off the allocation reuse, so every method collects into a new vector instead. The results are the same either way, so this is useful to check if a bug comes from the unsafe reuse code.
+
 It also adds some functionality to reuse the allocation of a `Box<T>`, using the `BoxExt`/`UninitBox` api.
 
 ```rust
@@ -121,6 +150,20 @@ macro_rules! defer {
 ///     zip_with!(a, move |a| a + b)
 /// }
 /// ```
+///
+/// The input vectors can also be listed without the tuple, separated from the
+/// "closure" by a semicolon.
+///
+/// ```rust
+/// use vec_utils::zip_with;
+///
+/// fn sum_4(a: Vec<i32>, b: Vec<i32>, c: Vec<i32>, d: Vec<i32>) -> Vec<i32> {
+///     zip_with!(a, b, c, d; |w, x, y, z| w + x + y + z)
+/// }
+/// ```
+///
+/// The output will have the length of the shortest input vector, the remaining
+/// elements of the longer vectors will be dropped
 #[macro_export]
 macro_rules! try_zip_with {
     ($vec:expr, $($move:ident)? |$($i:ident),+ $(,)?| $($work:tt)*) => {{
@@ -132,6 +175,9 @@ macro_rules! try_zip_with {
             $($move)? |$crate::list!(PLACE $($i),*)| $($work)*
         )
     }};
+    ($($vec:expr),+ ; $($move:ident)? |$($i:ident),+ $(,)?| $($work:tt)*) => {
+        $crate::try_zip_with!(($($vec),+), $($move)? |$($i),+| $($work)*)
+    };
 }
 
 /// A wrapper around `try_zip_with` for infallible mapping
@@ -140,12 +186,15 @@ macro_rules! zip_with {
     ($vec:expr, $($move:ident)? |$($i:ident),+ $(,)?| $($work:tt)*) => {
         match $crate::try_zip_with!(
             $vec, $($move)? |$($i),+|
-            Ok::<_, std::convert::Infallible>($($work)*)
+            Ok::<_, ::core::convert::Infallible>($($work)*)
         ) {
             Ok(x) => x,
             Err(x) => match x {}
         }
     };
+    ($($vec:expr),+ ; $($move:ident)? |$($i:ident),+ $(,)?| $($work:tt)*) => {
+        $crate::zip_with!(($($vec),+), $($move)? |$($i),+| $($work)*)
+    };
 }
 
 #[doc(hidden)]
@@ -170,10 +219,32 @@ impl<F: FnOnce()> Drop for OnDrop<F> {
     }
 }
 
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
+mod array;
 mod boxed;
+mod rc;
+mod slice;
+#[cfg(feature = "smallvec")]
+mod small_vec;
+#[cfg(feature = "tinyvec")]
+mod tiny_vec;
+mod transparent;
 mod r#try;
 mod vec;
+mod vec_deque;
 
+pub use self::array::*;
 pub use self::boxed::*;
 pub use self::r#try::*;
+pub use self::rc::*;
+pub use self::slice::*;
+#[cfg(feature = "smallvec")]
+pub use self::small_vec::*;
+#[cfg(feature = "tinyvec")]
+pub use self::tiny_vec::*;
+pub use self::transparent::*;
 pub use self::vec::*;
+pub use self::vec_deque::*;
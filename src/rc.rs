@@ -0,0 +1,146 @@
+use alloc::rc::Rc;
+#[cfg(target_has_atomic = "ptr")]
+use alloc::sync::Arc;
+use core::alloc::Layout;
+use core::marker::PhantomData;
+use core::mem::ManuallyDrop;
+
+use super::vec::can_reuse;
+
+/// Map a reference counted slice to another reference counted slice, reusing
+/// the allocation if the `Rc` is uniquely held, i.e. there are no other `Rc`s
+/// or `Weak`s that point to it, and the allocation layouts of the two types match
+/// i.e. if `std::alloc::Layout::<T>::new() == std::alloc::Layout::<U>::new()`
+///
+/// The allocation also holds the reference counts in a header before the slice,
+/// the layout of that header isn't part of the public api of `Rc`, so it can't be
+/// used to work out where a slice of a different layout would go. But when the
+/// layouts of `T` and `U` match exactly, then `[T]` and `[U]` of the same length
+/// have the same layout, so the header and the slice are in the same place for both,
+/// and the allocation can be handed back to `Rc` as is.
+///
+/// If the allocation can't be reused, then the original `Rc` is returned,
+/// so that it can be cloned and mapped instead
+pub fn try_map_rc<T, U, F: FnMut(T) -> U>(mut rc: Rc<[T]>, f: F) -> Result<Rc<[U]>, Rc<[T]>> {
+    // `get_mut` checks that there are no other `Rc`s or `Weak`s
+    if !can_reuse() || Layout::new::<T>() != Layout::new::<U>() || Rc::get_mut(&mut rc).is_none() {
+        return Err(rc);
+    }
+
+    unsafe fn free<T>(ptr: *const [ManuallyDrop<T>]) {
+        drop(Rc::from_raw(ptr))
+    }
+
+    unsafe {
+        let ptr = map_shared(Rc::into_raw(rc), free::<T>, f);
+
+        Ok(Rc::from_raw(ptr))
+    }
+}
+
+/// Map an atomically reference counted slice to another atomically reference
+/// counted slice, reusing the allocation in the same way as [`try_map_rc`]
+///
+/// The allocation will be reused if the `Arc` is uniquely held, i.e. there are no
+/// other `Arc`s or `Weak`s that point to it, and the allocation layouts of the two
+/// types match. Otherwise the original `Arc` is returned, so that it can be
+/// cloned and mapped instead
+#[cfg(target_has_atomic = "ptr")]
+pub fn try_map_arc<T, U, F: FnMut(T) -> U>(mut arc: Arc<[T]>, f: F) -> Result<Arc<[U]>, Arc<[T]>> {
+    // `get_mut` checks that there are no other `Arc`s or `Weak`s, and once
+    // that's true no other thread can make a new one, so this can't race
+    if !can_reuse() || Layout::new::<T>() != Layout::new::<U>() || Arc::get_mut(&mut arc).is_none()
+    {
+        return Err(arc);
+    }
+
+    unsafe fn free<T>(ptr: *const [ManuallyDrop<T>]) {
+        drop(Arc::from_raw(ptr))
+    }
+
+    unsafe {
+        let ptr = map_shared(Arc::into_raw(arc), free::<T>, f);
+
+        Ok(Arc::from_raw(ptr))
+    }
+}
+
+/// Maps the slice in place, and if `f` panics cleans up the slice and
+/// frees the allocation with `free`
+///
+/// # Safety
+///
+/// `ptr` must be uniquely owned, and `free` must be able to free the
+/// allocation without dropping any of the values in it
+unsafe fn map_shared<T, U, F: FnMut(T) -> U>(
+    ptr: *const [T],
+    free: unsafe fn(*const [ManuallyDrop<T>]),
+    mut f: F,
+) -> *const [U] {
+    debug_assert_eq!(Layout::new::<T>(), Layout::new::<U>());
+
+    let mut iter = SharedMapIter::<T, U> {
+        ptr: ptr as *mut T,
+        len: ptr.len(),
+        init_len: 0,
+        free,
+        drop: PhantomData,
+    };
+
+    // does a pointer walk, easy for LLVM to optimize
+    while iter.init_len < iter.len {
+        let value = iter.ptr.add(iter.init_len);
+        let value = f(value.read());
+
+        (iter.ptr as *mut U).add(iter.init_len).write(value);
+        iter.init_len += 1;
+    }
+
+    let iter = ManuallyDrop::new(iter);
+
+    core::ptr::slice_from_raw_parts(iter.ptr as *const U, iter.len)
+}
+
+struct SharedMapIter<T, U> {
+    ptr: *mut T,
+    len: usize,
+    init_len: usize,
+
+    // frees the allocation without dropping the values in it
+    free: unsafe fn(*const [ManuallyDrop<T>]),
+
+    // for drop check
+    drop: PhantomData<U>,
+}
+
+impl<T, U> Drop for SharedMapIter<T, U> {
+    fn drop(&mut self) {
+        // this is only dropped if `f` panics, right after a value was read
+        debug_assert!(self.init_len < self.len);
+
+        unsafe {
+            // free the allocation, this happens last
+            defer! {
+                (self.free)(core::ptr::slice_from_raw_parts(
+                    self.ptr as *const ManuallyDrop<T>,
+                    self.len,
+                ));
+            }
+
+            // destroy the initialized output
+            defer! {
+                core::ptr::drop_in_place(core::ptr::slice_from_raw_parts_mut(
+                    self.ptr as *mut U,
+                    self.init_len,
+                ));
+            }
+
+            // offset by 1 because the value at `init_len` was
+            // just read from, dropping that would lead to a double free
+            core::ptr::drop_in_place(core::ptr::slice_from_raw_parts_mut(
+                self.ptr.add(self.init_len + 1),
+                self.len - self.init_len - 1,
+            ));
+        }
+    }
+}
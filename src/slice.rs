@@ -0,0 +1,56 @@
+use alloc::vec::Vec;
+
+use super::{r#try, Try};
+
+/// Extension methods for `[T]`
+pub trait SliceExt {
+    /// The type that the `[T]` stores
+    type T;
+
+    /// Map a slice to a new vector, this will always allocate
+    /// exactly enough space for the output
+    fn map_to_vec<U, F: FnMut(&Self::T) -> U>(&self, mut f: F) -> Vec<U> {
+        use core::convert::Infallible;
+
+        match self.try_map_to_vec(move |x| Ok::<_, Infallible>(f(x))) {
+            Ok(x) => x,
+            Err(x) => match x {},
+        }
+    }
+
+    /// Map a slice to a new vector, this will always allocate
+    /// exactly enough space for the output
+    ///
+    /// The mapping function can be fallible, and on early return, it will drop all previous values.
+    /// The error will be returned as a `Result`
+    fn try_map_to_vec<U, R: Try<Ok = U>, F: FnMut(&Self::T) -> R>(
+        &self,
+        f: F,
+    ) -> Result<Vec<U>, R::Error>;
+}
+
+impl<T> SliceExt for [T] {
+    type T = T;
+
+    fn try_map_to_vec<U, R: Try<Ok = U>, F: FnMut(&Self::T) -> R>(
+        &self,
+        mut f: F,
+    ) -> Result<Vec<U>, R::Error> {
+        let mut output = Vec::<U>::with_capacity(self.len());
+
+        // does a pointer walk over the spare capacity of the output,
+        // the length is updated after each write so that if `f` panics
+        // or returns early, all of the previous values are dropped
+        for value in self {
+            let value = r#try!(f(value));
+
+            unsafe {
+                let len = output.len();
+                output.as_mut_ptr().add(len).write(value);
+                output.set_len(len + 1);
+            }
+        }
+
+        Ok(output)
+    }
+}
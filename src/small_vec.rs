@@ -0,0 +1,84 @@
+use smallvec::SmallVec;
+
+use super::{Try, VecExt};
+
+/// Extension methods for `SmallVec<[T; N]>`
+///
+/// If the vector has spilled onto the heap, then the heap allocation is reused in the
+/// same cases as [`VecExt`]. Otherwise the values are stored inline, so there is no
+/// allocation to reuse, and the output is written straight into the inline storage
+/// of the new vector, which never allocates
+pub trait SmallVecExt<const N: usize>: Sized {
+    /// The type that the `SmallVec<[T; N]>` stores
+    type T;
+
+    /// Map a small vector to another small vector, will try and reuse the heap allocation
+    /// in the same cases as [`VecExt::map`](trait.VecExt.html#method.map)
+    fn map<U, F: FnMut(Self::T) -> U>(self, mut f: F) -> SmallVec<[U; N]> {
+        use core::convert::Infallible;
+
+        match self.try_map(move |x| Ok::<_, Infallible>(f(x))) {
+            Ok(x) => x,
+            Err(x) => match x {},
+        }
+    }
+
+    /// Map a small vector to another small vector, will try and reuse the heap allocation
+    /// in the same cases as [`VecExt::map`](trait.VecExt.html#method.map)
+    ///
+    /// The mapping function can be fallible, and on early return, it will drop all previous values,
+    /// and the rest of the input vector. The error will be returned as a `Result`
+    fn try_map<U, R: Try<Ok = U>, F: FnMut(Self::T) -> R>(
+        self,
+        f: F,
+    ) -> Result<SmallVec<[U; N]>, R::Error>;
+
+    /// Zip a small vector to another small vector and combine them, the result will be
+    /// returned, the heap allocation of either vector will be reused if possible, in the
+    /// same way as [`VecExt::zip_with`](trait.VecExt.html#method.zip_with)
+    ///
+    /// If only one of the vectors has spilled, then only that vector's allocation can be
+    /// reused, the other vector is read from without being moved onto the heap
+    fn zip_with<U, V, F: FnMut(Self::T, U) -> V>(
+        self,
+        other: SmallVec<[U; N]>,
+        f: F,
+    ) -> SmallVec<[V; N]>;
+}
+
+impl<T, const N: usize> SmallVecExt<N> for SmallVec<[T; N]> {
+    type T = T;
+
+    fn try_map<U, R: Try<Ok = U>, F: FnMut(Self::T) -> R>(
+        self,
+        f: F,
+    ) -> Result<SmallVec<[U; N]>, R::Error> {
+        if self.spilled() {
+            // converting a spilled `SmallVec` to and from a `Vec` is free
+            self.into_vec().try_map(f).map(SmallVec::from_vec)
+        } else {
+            // the input is at most `N` long, so the output won't spill
+            self.into_iter().map(f).map(R::into_result).collect()
+        }
+    }
+
+    fn zip_with<U, V, F: FnMut(Self::T, U) -> V>(
+        self,
+        other: SmallVec<[U; N]>,
+        mut f: F,
+    ) -> SmallVec<[V; N]> {
+        match (self.spilled(), other.spilled()) {
+            (true, true) => SmallVec::from_vec(self.into_vec().zip_with(other.into_vec(), f)),
+            (true, false) => SmallVec::from_vec(self.into_vec().zip_with_iter(other, f)),
+            (false, true) => {
+                SmallVec::from_vec(other.into_vec().zip_with_iter(self, move |y, x| f(x, y)))
+            }
+            // both inputs are at most `N` long, so the output won't spill
+            (false, false) => self
+                .into_iter()
+                .zip(other)
+                .map(move |(x, y)| f(x, y))
+                .collect(),
+        }
+    }
+}
@@ -0,0 +1,157 @@
+use tinyvec::{ArrayVec, TinyVec};
+
+use super::{ArrayExt, Try, VecExt};
+
+/// Extension methods for `ArrayVec<[T; N]>`
+///
+/// `tinyvec` keeps every slot of the backing array initialized, so the array is
+/// mapped in place over the initialized prefix when the layouts of `T` and `U` match,
+/// in the same way as [`ArrayExt::map_array`](trait.ArrayExt.html#method.map_array),
+/// and the rest of the array is filled with `U::default()`
+pub trait ArrayVecExt<const N: usize>: Sized {
+    /// The type that the `ArrayVec<[T; N]>` stores
+    type T;
+
+    /// Map an array vector to another array vector, will reuse the storage if the
+    /// allocation layouts of the two types match
+    fn map<U: Default, F: FnMut(Self::T) -> U>(self, mut f: F) -> ArrayVec<[U; N]> {
+        use core::convert::Infallible;
+
+        match self.try_map(move |x| Ok::<_, Infallible>(f(x))) {
+            Ok(x) => x,
+            Err(x) => match x {},
+        }
+    }
+
+    /// Map an array vector to another array vector, will reuse the storage if the
+    /// allocation layouts of the two types match
+    ///
+    /// The mapping function can be fallible, and on early return, it will drop all previous values,
+    /// and the rest of the input vector. The error will be returned as a `Result`
+    fn try_map<U: Default, R: Try<Ok = U>, F: FnMut(Self::T) -> R>(
+        self,
+        f: F,
+    ) -> Result<ArrayVec<[U; N]>, R::Error>;
+
+    /// Zip an array vector to another array vector and combine them, the output
+    /// is as long as the shorter of the two vectors
+    fn zip_with<U: Default, V: Default, F: FnMut(Self::T, U) -> V>(
+        self,
+        other: ArrayVec<[U; N]>,
+        f: F,
+    ) -> ArrayVec<[V; N]>;
+}
+
+impl<T: Default, const N: usize> ArrayVecExt<N> for ArrayVec<[T; N]> {
+    type T = T;
+
+    fn try_map<U: Default, R: Try<Ok = U>, F: FnMut(Self::T) -> R>(
+        self,
+        mut f: F,
+    ) -> Result<ArrayVec<[U; N]>, R::Error> {
+        let len = self.len();
+        let mut index = 0;
+
+        // the slots past the length only hold default values, so they
+        // are replaced by default values of the output type
+        let data = self.into_inner().try_map_array(|x| {
+            index += 1;
+
+            if index <= len {
+                f(x).into_result()
+            } else {
+                Ok(U::default())
+            }
+        })?;
+
+        Ok(ArrayVec::from_array_len(data, len))
+    }
+
+    fn zip_with<U: Default, V: Default, F: FnMut(Self::T, U) -> V>(
+        self,
+        other: ArrayVec<[U; N]>,
+        mut f: F,
+    ) -> ArrayVec<[V; N]> {
+        // both inputs are at most `N` long, so the output will fit
+        self.into_iter()
+            .zip(other)
+            .map(move |(x, y)| f(x, y))
+            .collect()
+    }
+}
+
+/// Extension methods for `TinyVec<[T; N]>`
+///
+/// If the vector is on the heap, then the heap allocation is reused in the same cases
+/// as [`VecExt`]. Otherwise the values are stored inline, and are mapped in the same way
+/// as [`ArrayVecExt`], so the output stays inline without allocating
+pub trait TinyVecExt<const N: usize>: Sized {
+    /// The type that the `TinyVec<[T; N]>` stores
+    type T;
+
+    /// Map a tiny vector to another tiny vector, will try and reuse the heap allocation
+    /// in the same cases as [`VecExt::map`](trait.VecExt.html#method.map)
+    fn map<U: Default, F: FnMut(Self::T) -> U>(self, mut f: F) -> TinyVec<[U; N]> {
+        use core::convert::Infallible;
+
+        match self.try_map(move |x| Ok::<_, Infallible>(f(x))) {
+            Ok(x) => x,
+            Err(x) => match x {},
+        }
+    }
+
+    /// Map a tiny vector to another tiny vector, will try and reuse the heap allocation
+    /// in the same cases as [`VecExt::map`](trait.VecExt.html#method.map)
+    ///
+    /// The mapping function can be fallible, and on early return, it will drop all previous values,
+    /// and the rest of the input vector. The error will be returned as a `Result`
+    fn try_map<U: Default, R: Try<Ok = U>, F: FnMut(Self::T) -> R>(
+        self,
+        f: F,
+    ) -> Result<TinyVec<[U; N]>, R::Error>;
+
+    /// Zip a tiny vector to another tiny vector and combine them, the result will be
+    /// returned, the heap allocation of either vector will be reused if possible, in the
+    /// same way as [`VecExt::zip_with`](trait.VecExt.html#method.zip_with)
+    ///
+    /// If only one of the vectors is on the heap, then only that vector's allocation can be
+    /// reused, the other vector is read from without being moved onto the heap
+    fn zip_with<U: Default, V: Default, F: FnMut(Self::T, U) -> V>(
+        self,
+        other: TinyVec<[U; N]>,
+        f: F,
+    ) -> TinyVec<[V; N]>;
+}
+
+impl<T: Default, const N: usize> TinyVecExt<N> for TinyVec<[T; N]> {
+    type T = T;
+
+    fn try_map<U: Default, R: Try<Ok = U>, F: FnMut(Self::T) -> R>(
+        self,
+        f: F,
+    ) -> Result<TinyVec<[U; N]>, R::Error> {
+        match self {
+            TinyVec::Heap(vec) => vec.try_map(f).map(TinyVec::Heap),
+            TinyVec::Inline(vec) => vec.try_map(f).map(TinyVec::Inline),
+        }
+    }
+
+    fn zip_with<U: Default, V: Default, F: FnMut(Self::T, U) -> V>(
+        self,
+        other: TinyVec<[U; N]>,
+        mut f: F,
+    ) -> TinyVec<[V; N]> {
+        match (self, other) {
+            (TinyVec::Heap(left), TinyVec::Heap(right)) => TinyVec::Heap(left.zip_with(right, f)),
+            (TinyVec::Heap(left), TinyVec::Inline(right)) => {
+                TinyVec::Heap(left.zip_with_iter(right, f))
+            }
+            (TinyVec::Inline(left), TinyVec::Heap(right)) => {
+                TinyVec::Heap(right.zip_with_iter(left, move |y, x| f(x, y)))
+            }
+            (TinyVec::Inline(left), TinyVec::Inline(right)) => {
+                TinyVec::Inline(ArrayVecExt::zip_with(left, right, f))
+            }
+        }
+    }
+}
@@ -0,0 +1,81 @@
+use alloc::vec::Vec;
+use core::cell::Cell;
+use core::cmp::Reverse;
+use core::marker::PhantomData;
+use core::mem::{align_of, size_of, ManuallyDrop};
+use core::num::Wrapping;
+
+use super::vec::can_reuse;
+
+/// A `#[repr(transparent)]` wrapper around `Inner`, this lets
+/// [`VecExt::wrap`](crate::VecExt::wrap) and
+/// [`VecExt::unwrap_newtype`](crate::VecExt::unwrap_newtype)
+/// convert between vectors of the two types without mapping them
+///
+/// ```rust
+/// use vec_utils::{TransparentWrapper, VecExt};
+///
+/// #[repr(transparent)]
+/// struct Meters(f64);
+///
+/// unsafe impl TransparentWrapper<f64> for Meters {}
+///
+/// fn to_meters(v: Vec<f64>) -> Vec<Meters> {
+///     v.wrap()
+/// }
+/// ```
+///
+/// # Safety
+///
+/// `Self` must be `#[repr(transparent)]`, and it's only field that isn't zero-sized
+/// must be an `Inner`. So that every `Inner` is a valid `Self`, and every `Self` is
+/// a valid `Inner`. If the sizes or alignments of the two types don't match, then
+/// the conversions fail to compile
+///
+/// ```rust,compile_fail
+/// use vec_utils::{TransparentWrapper, VecExt};
+///
+/// struct NotTransparent(f64, u8);
+///
+/// unsafe impl TransparentWrapper<f64> for NotTransparent {}
+///
+/// let v: Vec<NotTransparent> = vec![1.0].wrap();
+/// ```
+pub unsafe trait TransparentWrapper<Inner>: Sized {}
+
+unsafe impl<T> TransparentWrapper<T> for Wrapping<T> {}
+unsafe impl<T> TransparentWrapper<T> for Reverse<T> {}
+unsafe impl<T> TransparentWrapper<T> for ManuallyDrop<T> {}
+unsafe impl<T> TransparentWrapper<T> for Cell<T> {}
+
+// checks that the layouts of `T` and `U` match when a conversion is compiled
+struct SameLayout<T, U>(PhantomData<(T, U)>);
+
+impl<T, U> SameLayout<T, U> {
+    const ASSERT: () = assert!(
+        size_of::<T>() == size_of::<U>() && align_of::<T>() == align_of::<U>(),
+        "A `TransparentWrapper` must have the same layout as the type it wraps"
+    );
+}
+
+/// Does the work of `wrap` and `unwrap_newtype`
+///
+/// # Safety
+///
+/// Every `T` must be a valid `U`
+pub(crate) unsafe fn cast_vec<T, U>(vec: Vec<T>) -> Vec<U> {
+    #[allow(clippy::let_unit_value)]
+    let () = SameLayout::<T, U>::ASSERT;
+
+    if !can_reuse() {
+        return vec
+            .into_iter()
+            .map(|x| core::mem::transmute_copy::<ManuallyDrop<T>, U>(&ManuallyDrop::new(x)))
+            .collect();
+    }
+
+    let mut vec = ManuallyDrop::new(vec);
+
+    // the layouts match, so the allocation can be used as is
+    Vec::from_raw_parts(vec.as_mut_ptr() as *mut U, vec.len(), vec.capacity())
+}
@@ -1,4 +1,5 @@
-use std::task::Poll;
+use core::ops::ControlFlow;
+use core::task::Poll;
 
 /// A stable version of [`core::ops::Try`].
 pub trait Try {
@@ -55,6 +56,29 @@ impl<T> Try for Option<T> {
     }
 }
 
+impl<B, C> Try for ControlFlow<B, C> {
+    type Ok = C;
+    type Error = B;
+
+    #[inline]
+    fn into_result(self) -> Result<Self::Ok, Self::Error> {
+        match self {
+            ControlFlow::Continue(c) => Ok(c),
+            ControlFlow::Break(b) => Err(b),
+        }
+    }
+
+    #[inline]
+    fn from_error(b: Self::Error) -> Self {
+        ControlFlow::Break(b)
+    }
+
+    #[inline]
+    fn from_ok(c: Self::Ok) -> Self {
+        ControlFlow::Continue(c)
+    }
+}
+
 impl<T, E> Try for Poll<Option<Result<T, E>>> {
     type Ok = Poll<Option<T>>;
     type Error = E;
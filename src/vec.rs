@@ -1,12 +1,30 @@
-use std::alloc::Layout;
-use std::marker::PhantomData;
-use std::mem::ManuallyDrop;
-
-use super::{r#try, Try};
-
+use alloc::boxed::Box;
+use alloc::collections::TryReserveError;
+use alloc::vec::Vec;
+use core::alloc::Layout;
+use core::marker::PhantomData;
+use core::mem::{ManuallyDrop, MaybeUninit};
+use core::ops::ControlFlow;
+
+use super::{r#try, TransparentWrapper, Try};
+
+#[cfg(feature = "allocator_api")]
+mod allocator;
 mod general_zip;
-
+#[cfg(feature = "rayon")]
+mod par;
+mod pool;
+mod replace;
+mod scratch;
+#[cfg(feature = "portable_simd")]
+mod simd;
+
+#[cfg(feature = "allocator_api")]
+pub use allocator::*;
 pub use general_zip::*;
+pub use pool::*;
+pub use replace::*;
+pub use scratch::*;
 
 /// A type that contains useful meta-data about a
 /// the Vec<_> that it was created from
@@ -45,6 +63,11 @@ pub struct Output<T> {
 impl<T> Output<T> {
     /// Create a new output buffer, this buffer will own it's data segment
     /// from `start` to `start.add(cap)`
+    ///
+    /// # Safety
+    ///
+    /// `start` must point to an allocation that can be turned into a `Vec<T>`
+    /// with the given capacity, and nothing else may free that allocation
     pub unsafe fn new(start: *mut T, cap: usize) -> Self {
         Self {
             start,
@@ -72,6 +95,80 @@ impl<T> From<Vec<T>> for Input<T> {
     }
 }
 
+impl<T> Input<T> {
+    // Sets up the input so that `U`s can be written from the start of the allocation
+    // while reading `T`s, if the allocation `fits` the `U`s
+    fn for_output<U>(vec: Vec<T>) -> Self {
+        use core::mem::size_of;
+
+        let mut data = Input::from(vec);
+
+        if size_of::<U>() > size_of::<T>() {
+            // `U` is larger than `T`, but the allocation has enough spare capacity
+            // so move the input to the end of the allocation, this way the output
+            // will never catch up to the values that haven't been read yet
+            data.move_to_end();
+        }
+
+        data
+    }
+
+    // Moves the input to the end of the allocation, leaving `cap - len` free slots
+    // in front of it. This must be called before anything is read
+    fn move_to_end(&mut self) {
+        debug_assert_eq!(self.ptr, self.start);
+
+        unsafe {
+            self.ptr = self.start.add(self.cap - self.len);
+            core::ptr::copy(self.start, self.ptr, self.len);
+        }
+    }
+}
+
+/// The leftover elements of the longer vector from
+/// [`VecExt::zip_with_remainder`](trait.VecExt.html#method.zip_with_remainder)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Remainder<T, U> {
+    /// The left vector was longer, and these are it's remaining elements
+    Left(Vec<T>),
+    /// The right vector was longer, and these are it's remaining elements
+    Right(Vec<U>),
+    /// Both vectors had the same length
+    Neither,
+}
+
+/// A value that is either on the left or the right, used by
+/// [`VecExt::partition_map`](trait.VecExt.html#method.partition_map)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Either<L, R> {
+    /// A value that goes into the left vector
+    Left(L),
+    /// A value that goes into the right vector
+    Right(R),
+}
+
+/// A pair of values, or just one of them if the other vector ran out, used by
+/// [`VecExt::zip_longest_with`](trait.VecExt.html#method.zip_longest_with)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum EitherOrBoth<L, R> {
+    /// Both vectors had a value at this position
+    Both(L, R),
+    /// Only the left vector had a value at this position
+    Left(L),
+    /// Only the right vector had a value at this position
+    Right(R),
+}
+
+/// Whether a map reused the input allocation, returned by
+/// [`VecExt::map_reporting`](trait.VecExt.html#method.map_reporting)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Reused {
+    /// The output was written over the input allocation
+    Buffer,
+    /// The output was written into a new allocation
+    Fresh,
+}
+
 /// Extension methods for `Vec<T>`
 pub trait VecExt: Sized {
     /// The type that the `Vec<T>` stores
@@ -81,8 +178,16 @@ pub trait VecExt: Sized {
     /// allocation layouts of the two types match, i.e. if
     /// `std::alloc::Layout::<T>::new() == std::alloc::Layout::<U>::new()`
     /// then the allocation will be reused
+    ///
+    /// The allocation will also be reused if `T` and `U` have the same alignment
+    /// and either `U` is smaller than `T`, or the allocation has enough spare capacity
+    /// to fit all of the `U`s
+    ///
+    /// When the layouts match (including when `U` is `T`), each output is written into
+    /// the same slot that it's input was read from, so this compiles down to about the
+    /// same loop as [`map_in_place`](VecExt::map_in_place)
     fn map<U, F: FnMut(Self::T) -> U>(self, mut f: F) -> Vec<U> {
-        use std::convert::Infallible;
+        use core::convert::Infallible;
 
         match self.try_map(move |x| Ok::<_, Infallible>(f(x))) {
             Ok(x) => x,
@@ -95,259 +200,3336 @@ pub trait VecExt: Sized {
     /// `std::alloc::Layout::<T>::new() == std::alloc::Layout::<U>::new()`
     /// then the allocation will be reused
     ///
+    /// The allocation will also be reused if `T` and `U` have the same alignment
+    /// and either `U` is smaller than `T`, or the allocation has enough spare capacity
+    /// to fit all of the `U`s
+    ///
+    /// If `U` has a larger alignment than `T`, then a new vector is always allocated, even if
+    /// the allocation happens to be aligned for `U`. The allocator must be given the same
+    /// layout to deallocate as it was given to allocate, and the `Vec<U>` would deallocate
+    /// with the alignment of `U`
+    ///
     /// The mapping function can be fallible, and on early return, it will drop all previous values,
-    /// and the rest of the input vector. Thre error will be returned as a `Result`
+    /// and the rest of the input vector. The error will be returned as a `Result`
     fn try_map<U, R: Try<Ok = U>, F: FnMut(Self::T) -> R>(self, f: F) -> Result<Vec<U>, R::Error>;
 
-    /// Zip a vector to another vector and combine them, the result will be returned,
-    /// the allocation will be reused if possible, the larger allocation of the input vectors
-    /// will be used if all of `T`, `U`, and `V` have the same allocation layouts.
-    fn zip_with<U, V, F: FnMut(Self::T, U) -> V>(self, other: Vec<U>, mut f: F) -> Vec<V> {
-        use std::convert::Infallible;
+    /// Split a vector into two vectors, will try and reuse the allocation for one of the
+    /// outputs, in the same cases as [`map`](VecExt::map). The other output will be allocated
+    ///
+    /// The allocation will be reused for the `A`s if possible, otherwise it
+    /// will be reused for the `B`s if their layout matches `T`
+    fn unzip_with<A, B, F: FnMut(Self::T) -> (A, B)>(self, f: F) -> (Vec<A>, Vec<B>);
 
-        match self.try_zip_with(other, move |x, y| Ok::<_, Infallible>(f(x, y))) {
-            Ok(x) => x,
-            Err(x) => match x {},
-        }
-    }
+    /// Split a vector into two vectors, will try and reuse the allocation for one of the
+    /// outputs in the same way as [`unzip_with`](VecExt::unzip_with)
+    ///
+    /// Unlike `unzip_with`, every output that can't reuse the allocation is allocated up
+    /// front with exactly enough capacity for all of the elements, like
+    /// [`map_with_capacity`](VecExt::map_with_capacity), so neither output ever has to grow
+    fn map_split2<A, B, F: FnMut(Self::T) -> (A, B)>(self, f: F) -> (Vec<A>, Vec<B>);
 
-    /// Zip a vector to another vector and combine them, the result will be returned,
-    /// the allocation will be reused if possible, the larger allocation of the input vectors
-    /// will be used if all of `T`, `U`, and `V` have the same allocation layouts.
+    /// Map a vector, and append the results to the end of `out`
     ///
-    /// The mapping function can be fallible, and on early return, it will drop all previous values,
-    /// and the rest of the input vectors. Thre error will be returned as a `Result`
-    fn try_zip_with<U, V, R: Try<Ok = V>, F: FnMut(Self::T, U) -> R>(
+    /// This will never reuse the allocation of the input vector, instead it will be freed at the end.
+    /// If `f` panics, then `out` will contain all of the values that were mapped before the panic
+    fn map_into<U, F: FnMut(Self::T) -> U>(self, out: &mut Vec<U>, f: F);
+
+    /// Map a vector to another vector, calling `f` on the elements from back to front,
+    /// the outputs stay in the same order as the inputs. Will reuse the allocation if the
+    /// allocation layouts of the two types match, i.e. if
+    /// `std::alloc::Layout::<T>::new() == std::alloc::Layout::<U>::new()`
+    fn map_rev<U, F: FnMut(Self::T) -> U>(self, f: F) -> Vec<U>;
+
+    /// Map a vector into an existing slice, each mapped value replaces the value
+    /// at the same position in `out`, which is dropped. The input allocation is freed
+    /// afterwards
+    ///
+    /// If `f` panics, then the values before the one that caused the panic will have
+    /// already been replaced, and the rest of `out` will be left untouched
+    ///
+    /// # Panics
+    ///
+    /// If the length of `out` is not the same as the length of the vector
+    fn map_into_slice<U, F: FnMut(Self::T) -> U>(self, out: &mut [U], f: F);
+
+    /// Map a vector into uninitialized storage, the mapped values are written
+    /// to the front of `out`, and the initialized part of `out` is returned.
+    /// The input allocation is freed afterwards
+    ///
+    /// The returned slice borrows `out`, but `out` still holds `MaybeUninit<U>`s,
+    /// so the mapped values will *not* be dropped automatically. They will be leaked
+    /// unless they are dropped manually, for example with `core::ptr::drop_in_place`
+    /// on the returned slice. After that, `out` must be treated as uninitialized again
+    ///
+    /// If `f` panics, then the values that were already written to `out` and the rest
+    /// of the input vector are dropped
+    ///
+    /// # Panics
+    ///
+    /// If `out` is shorter than the vector
+    fn map_into_uninit<U, F: FnMut(Self::T) -> U>(
         self,
-        other: Vec<U>,
+        out: &mut [MaybeUninit<U>],
         f: F,
-    ) -> Result<Vec<V>, R::Error>;
+    ) -> &mut [U];
 
-    /// Drops all of the values in the vector and
-    /// create a new vector from it if the layouts are compatible
+    /// Map a vector into a boxed slice, will try and reuse the allocation in the same
+    /// cases as [`map`](VecExt::map)
     ///
-    /// if layouts are not compatible, then return `Vec::new()`
-    fn drop_and_reuse<U>(self) -> Vec<U>;
-}
+    /// The boxed slice has no spare capacity, so if the mapped vector has any spare
+    /// capacity, the allocation is shrunk to fit with `realloc`. If the capacity already
+    /// matches the length, then the allocation is reused as is
+    fn map_into_boxed_slice<U, F: FnMut(Self::T) -> U>(self, f: F) -> Box<[U]> {
+        self.map(f).into_boxed_slice()
+    }
 
-impl<T> VecExt for Vec<T> {
-    type T = T;
+    /// Map a vector to another vector, will try and reuse the allocation in the same
+    /// cases as [`map`](VecExt::map)
+    ///
+    /// The mapping function decides whether to keep going with `ControlFlow`,
+    /// on the first `Break(b)`, it will drop all previous values, and the rest
+    /// of the input vector, and return `Err(b)`
+    fn map_control_flow<B, C, F: FnMut(Self::T) -> ControlFlow<B, C>>(
+        self,
+        f: F,
+    ) -> Result<Vec<C>, B> {
+        self.try_map(f)
+    }
 
-    fn try_map<U, R: Try<Ok = U>, F: FnMut(Self::T) -> R>(self, f: F) -> Result<Vec<U>, R::Error> {
-        // try_zip_with! { self => |x| { f(x) } }
+    /// Map each of the inner vectors of a nested vector, will try and reuse the
+    /// allocation of every inner vector in the same cases as [`map`](VecExt::map)
+    ///
+    /// The allocation of the outer vector is always reused, because `Vec<T>` and `Vec<U>`
+    /// have the same layout. Each inner vector is mapped with it's own clone of `f`
+    fn map_nested<U, F>(self, f: F) -> Vec<Vec<U>>
+    where
+        Self::T: VecExt,
+        F: FnMut(<Self::T as VecExt>::T) -> U + Clone,
+    {
+        // if `f` panics, the inner `map` cleans up the inner vector,
+        // and the outer `map` cleans up the rest of the vectors
+        self.map(move |inner| inner.map(f.clone()))
+    }
 
-        if Layout::new::<T>() == Layout::new::<U>() {
-            let iter = MapIter {
-                init_len: 0,
-                data: Input::from(self),
-                drop: PhantomData,
-            };
+    /// Convert each element of a vector with `Into`, will try and reuse the allocation
+    /// in the same cases as [`map`](VecExt::map)
+    fn convert<U>(self) -> Vec<U>
+    where
+        Self::T: Into<U>,
+    {
+        self.map(Into::into)
+    }
 
-            iter.try_into_vec(f)
-        } else {
-            self.into_iter().map(f).map(R::into_result).collect()
-        }
+    /// Convert each element of a vector with `TryInto`, will try and reuse the allocation
+    /// in the same cases as [`map`](VecExt::map)
+    ///
+    /// On the first error, it will drop all previous values, and the rest of
+    /// the input vector. The error will be returned as a `Result`
+    fn try_convert<U>(self) -> Result<Vec<U>, <Self::T as core::convert::TryInto<U>>::Error>
+    where
+        Self::T: core::convert::TryInto<U>,
+    {
+        self.try_map(core::convert::TryInto::try_into)
     }
 
-    fn try_zip_with<U, V, R: Try<Ok = V>, F: FnMut(Self::T, U) -> R>(
+    /// Map a vector to another vector, will try and reuse the allocation in the same
+    /// cases as [`map`](VecExt::map)
+    ///
+    /// The mapping function can be fallible, and on early return, it will return the values
+    /// that were already mapped, the error, and the values that weren't mapped yet.
+    /// The value that `f` returned the error for is not included.
+    ///
+    /// On error, the values that weren't mapped yet are moved into a new allocation,
+    /// because the output has already overwritten part of the original allocation,
+    /// so the error path costs one allocation and a copy of the rest of the input
+    #[allow(clippy::type_complexity)]
+    fn try_map_resumable<U, R: Try<Ok = U>, F: FnMut(Self::T) -> R>(
         self,
-        other: Vec<U>,
-        mut f: F,
-    ) -> Result<Vec<V>, R::Error> {
-        // try_zip_with! { self, other => |x, y| { f(x, y) } }
+        f: F,
+    ) -> Result<Vec<U>, (Vec<U>, R::Error, Vec<Self::T>)>;
 
-        let len = self.len().min(other.len());
-        match (
-            Layout::new::<T>() == Layout::new::<V>(),
-            Layout::new::<U>() == Layout::new::<V>(),
-            self.capacity() >= other.capacity(),
-        ) {
-            (true, true, true) | (true, false, _) => ZipWithIter {
-                init_len: len,
-                min_len: len,
-                drop: PhantomData,
+    /// Map a vector to another vector, will try and reuse the allocation in the same
+    /// cases as [`map`](VecExt::map)
+    ///
+    /// The mapping function can be fallible, but unlike [`try_map`](VecExt::try_map) this
+    /// doesn't stop at the first error. `f` is called on every element, and if any of
+    /// the calls failed, then all of the errors are returned in order, and all of the
+    /// values that were mapped successfully are dropped.
+    ///
+    /// The allocation can only be reused if every call succeeds, the errors are
+    /// always collected into a new allocation
+    fn map_collect_errors<U, E, F: FnMut(Self::T) -> Result<U, E>>(
+        self,
+        mut f: F,
+    ) -> Result<Vec<U>, Vec<E>> {
+        match self.try_map_resumable(&mut f) {
+            Ok(vec) => Ok(vec),
+            Err((done, error, rest)) => {
+                drop(done);
 
-                left: Input::from(self),
-                right: Input::from(other),
-            }
-            .try_into_vec(f),
-            (true, true, false) | (false, true, _) => ZipWithIter {
-                init_len: len,
-                min_len: len,
-                drop: PhantomData,
+                let mut errors = Vec::new();
+                errors.push(error);
+                errors.extend(rest.into_iter().filter_map(move |x| f(x).err()));
 
-                left: Input::from(other),
-                right: Input::from(self),
+                Err(errors)
             }
-            .try_into_vec(move |y, x| f(x, y)),
-            (false, false, _) => self
-                .into_iter()
-                .zip(other.into_iter())
-                .map(move |(x, y)| f(x, y))
-                .map(R::into_result)
-                .collect(),
         }
     }
 
-    fn drop_and_reuse<U>(mut self) -> Vec<U> {
-        self.clear();
-
-        // no more elements in the vector
-        self.map(|_| unsafe { std::hint::unreachable_unchecked() })
+    /// Map a vector to another vector, will try and reuse the allocation in the same
+    /// cases as [`try_map`](VecExt::try_map)
+    ///
+    /// The mapping function returns a nested `Result`, and an error at either level
+    /// stops the mapping in the same way as an error from `try_map`
+    fn try_map_flatten<U, E, F: FnMut(Self::T) -> Result<Result<U, E>, E>>(
+        self,
+        mut f: F,
+    ) -> Result<Vec<U>, E> {
+        self.try_map(move |x| f(x).and_then(core::convert::identity))
     }
-}
-
-struct MapIter<T, U> {
-    init_len: usize,
 
-    data: Input<T>,
+    /// Map a vector to another vector, passing the index of each element and the length
+    /// of the vector along with it, will try and reuse the allocation in the same cases
+    /// as [`map`](VecExt::map)
+    ///
+    /// The length is the length of the input vector, and it is the same for every call
+    fn map_with_len<U, F: FnMut(usize, usize, Self::T) -> U>(self, f: F) -> Vec<U>;
 
-    // for drop check
-    drop: PhantomData<U>,
-}
+    /// Map a vector to another vector, passing the index of each element along with it,
+    /// will try and reuse the allocation in the same cases as [`map`](VecExt::map)
+    fn map_indexed<U, F: FnMut(usize, Self::T) -> U>(self, mut f: F) -> Vec<U> {
+        use core::convert::Infallible;
 
-impl<T, U> MapIter<T, U> {
-    fn try_into_vec<R: Try<Ok = U>, F: FnMut(T) -> R>(
-        mut self,
-        mut f: F,
-    ) -> Result<Vec<U>, R::Error> {
-        // does a pointer walk, easy for LLVM to optimize
-        while self.init_len < self.data.len {
-            unsafe {
-                let value = r#try!(f(self.data.ptr.read()));
+        match self.try_map_indexed(move |i, x| Ok::<_, Infallible>(f(i, x))) {
+            Ok(x) => x,
+            Err(x) => match x {},
+        }
+    }
 
-                (self.data.ptr as *mut U).write(value);
+    /// Map a vector to another vector, will try and reuse the allocation in the same
+    /// cases as [`map`](VecExt::map)
+    ///
+    /// If the allocation can't be reused, then the output is allocated with `try_reserve`,
+    /// so if that allocation fails, the input vector is dropped and the error is returned
+    /// instead of aborting. If the allocation is reused, this can't fail
+    fn try_map_alloc<U, F: FnMut(Self::T) -> U>(self, f: F) -> Result<Vec<U>, TryReserveError>;
+
+    /// Map a vector to another vector, always reusing the allocation, without checking
+    /// that the layouts of `T` and `U` match. This works like [`map`](VecExt::map)
+    /// otherwise, including if `f` panics
+    ///
+    /// # Safety
+    ///
+    /// The caller must guarantee that `Layout::new::<T>() == Layout::new::<U>()`,
+    /// this is checked with a `debug_assert`
+    unsafe fn map_unchecked<U, F: FnMut(Self::T) -> U>(self, f: F) -> Vec<U>;
 
-                self.data.ptr = self.data.ptr.add(1);
-                self.init_len += 1;
-            }
-        }
+    /// Map a vector to another vector, will try and reuse the allocation in the same
+    /// cases as [`map`](VecExt::map)
+    ///
+    /// If the allocation can't be reused, then the output is allocated up front with
+    /// exactly enough capacity for all of the elements, so it never has to grow
+    fn map_with_capacity<U, F: FnMut(Self::T) -> U>(self, f: F) -> Vec<U>;
 
-        let vec = ManuallyDrop::new(self);
+    /// Map a vector to another vector, will try and reuse the allocation in the same
+    /// cases as [`map`](VecExt::map), then shrink the output so that it's capacity
+    /// is the same as it's length
+    ///
+    /// If the allocation was reused, then it's shrunk with a single `realloc` straight to
+    /// the length of the output, which the allocator may do in place or by moving the values
+    /// into a new allocation. If the capacity already matches the length, then the
+    /// allocation is reused as is
+    fn map_shrink<U, F: FnMut(Self::T) -> U>(self, f: F) -> Vec<U>;
+
+    /// Map a vector to another vector in the same way as [`map`](VecExt::map),
+    /// and report whether the allocation was reused
+    ///
+    /// An empty vector with no capacity doesn't have an allocation to reuse,
+    /// so mapping it reports [`Reused::Fresh`], even though nothing is allocated
+    fn map_reporting<U, F: FnMut(Self::T) -> U>(self, f: F) -> (Vec<U>, Reused);
 
-        // we don't want to free the memory
-        // which is what dropping this `MapIter` will do
-        unsafe {
-            Ok(Vec::from_raw_parts(
-                vec.data.start as *mut U,
-                vec.data.len,
-                vec.data.cap,
-            ))
-        }
-    }
-}
+    /// Map a vector to another vector, will try and reuse the allocation in the same
+    /// cases as [`map`](VecExt::map)
+    ///
+    /// If the allocation can't be reused, then the output is written into a vector
+    /// taken from the pool, so that it only allocates if the pool is empty, or the
+    /// vector from the pool is too small
+    fn map_pooled<U, F: FnMut(Self::T) -> U>(self, pool: &mut VecPool<U>, f: F) -> Vec<U>;
 
-impl<T, U> Drop for MapIter<T, U> {
-    fn drop(&mut self) {
-        unsafe {
-            // destroy the initialized output
-            defer! {
-                Vec::from_raw_parts(
-                    self.data.start as *mut U,
-                    self.init_len,
-                    self.data.cap
-                );
-            }
+    /// Map a vector to another vector in the same way as [`map`](VecExt::map),
+    /// and keep track of how many elements were mapped in `counter`
+    ///
+    /// If `f` panics, then the number of elements that were mapped before the panic
+    /// is stored in `counter` while unwinding, before any of the mapped values or
+    /// the rest of the input are dropped. So the count can be read after catching
+    /// the panic, or from a destructor that runs later in the unwind. If the mapping
+    /// finishes, then the length of the output is stored in `counter`
+    #[cfg(target_has_atomic = "ptr")]
+    fn map_track_count<U, F: FnMut(Self::T) -> U>(
+        self,
+        counter: &core::sync::atomic::AtomicUsize,
+        mut f: F,
+    ) -> Vec<U> {
+        use core::sync::atomic::Ordering;
 
-            // offset by 1 because self.ptr is pointing to
-            // memory that was just read from, dropping that
-            // would lead to a double free
-            std::ptr::drop_in_place(std::slice::from_raw_parts_mut(
-                self.data.ptr.add(1),
-                self.data.len - self.init_len - 1,
-            ));
-        }
-    }
-}
+        let mut init_len = 0;
 
-// The size of these structures don't matter since they are transient
-// So I didn't bother optimizing the size of them, and instead put all the
-// useful information I wanted, so that it could be initialized all at once
-struct ZipWithIter<T, U, V> {
-    // This left buffer is the one that will be reused
-    // to write the output into
-    left: Input<T>,
+        let vec = self.map(|x| {
+            // if `f` panics, this stores the count as `f` unwinds,
+            // which happens before `map` cleans up
+            let guard = crate::OnDrop(Some(|| counter.store(init_len, Ordering::Relaxed)));
+            let value = f(x);
+            core::mem::forget(guard);
 
-    // We will only read from this buffer
-    //
-    // I considered using `std::vec::IntoIter`, but that lead to worse code
-    // because LLVM wasn't able to elide the bounds check on the iterator
-    right: Input<U>,
+            init_len += 1;
+            value
+        });
 
-    // the length of the output that has been written to
-    init_len: usize,
-    // the length of the vectors that must be traversed
-    min_len: usize,
+        counter.store(vec.len(), Ordering::Relaxed);
 
-    // for drop check
-    drop: PhantomData<V>,
-}
+        vec
+    }
 
-impl<T, U, V> ZipWithIter<T, U, V> {
-    fn try_into_vec<R: Try<Ok = V>, F: FnMut(T, U) -> R>(
-        mut self,
+    /// Map a vector to another vector, will try and reuse the allocation in the same
+    /// cases as [`map`](VecExt::map)
+    ///
+    /// `progress` is called with the number of elements mapped so far, each time
+    /// that number reaches a multiple of `every`. It isn't called for an incomplete
+    /// block, even if `f` panics partway through that block
+    ///
+    /// # Panics
+    ///
+    /// If `every` is zero
+    fn map_with_progress<U, F: FnMut(Self::T) -> U, P: FnMut(usize)>(
+        self,
+        every: usize,
+        mut progress: P,
         mut f: F,
-    ) -> Result<Vec<V>, R::Error> {
-        debug_assert_eq!(Layout::new::<T>(), Layout::new::<V>());
+    ) -> Vec<U> {
+        assert!(every != 0, "Tried to report progress every zero elements");
 
-        // this does a pointer walk and reads from left and right in lock-step
-        // then passes those values to the function to be processed
-        while let Some(min_len) = self.min_len.checked_sub(1) {
-            unsafe {
-                self.min_len = min_len;
-
-                let out = self.left.ptr as *mut V;
-                let left = self.left.ptr;
-                let right = self.right.ptr;
+        let mut init_len = 0;
+        let mut block_len = 0;
 
-                self.left.ptr = self.left.ptr.add(1);
-                self.right.ptr = self.right.ptr.add(1);
+        self.map(move |x| {
+            let value = f(x);
 
-                let value = r#try!(f(left.read(), right.read()));
+            // count with a separate block length, so that there
+            // is no division between each element
+            init_len += 1;
+            block_len += 1;
 
-                out.write(value);
+            if block_len == every {
+                block_len = 0;
+                progress(init_len);
             }
-        }
 
-        // We don't want to drop `self` if dropping the excess elements panics
-        // as that could lead to double drops
-        let vec = ManuallyDrop::new(self);
-        let output;
+            value
+        })
+    }
 
-        unsafe {
-            // create the vector now, so that if we panic in drop, we don't leak it
-            output = Vec::from_raw_parts(vec.left.start as *mut V, vec.init_len, vec.left.cap);
+    /// Map a vector to another vector, passing the index of each element along with it,
+    /// will try and reuse the allocation in the same cases as [`map`](VecExt::map)
+    ///
+    /// The mapping function can be fallible, and on early return, it will drop all previous values,
+    /// and the rest of the input vector. The error will be returned as a `Result`
+    fn try_map_indexed<U, R: Try<Ok = U>, F: FnMut(usize, Self::T) -> R>(
+        self,
+        f: F,
+    ) -> Result<Vec<U>, R::Error>;
 
-            // yay for defers running in reverse order and cleaning up the
-            // old vecs properly
+    /// Map a vector to another vector while threading some state through each call to `f`,
+    /// like `Iterator::scan`, will try and reuse the allocation in the same cases as
+    /// [`map`](VecExt::map)
+    ///
+    /// If `f` panics, the state is dropped normally
+    fn scan_map<S, U, F: FnMut(&mut S, Self::T) -> U>(self, init: S, mut f: F) -> Vec<U> {
+        let mut state = init;
 
-            // cleans up the right vec
-            defer! {
-                Vec::from_raw_parts(vec.right.start, 0, vec.right.cap);
-            }
+        self.map(|x| f(&mut state, x))
+    }
 
-            // drops the remaining elements of the right vec
-            defer! {
-                std::ptr::drop_in_place(std::slice::from_raw_parts_mut(
-                    vec.right.ptr,
-                    vec.right.len - vec.init_len
-                ));
-            }
+    /// Zip a vector with it's own indices `0..len` and combine them, without
+    /// allocating a vector of indices, will try and reuse the allocation in the
+    /// same cases as [`map`](VecExt::map)
+    fn zip_with_index<V, F: FnMut(usize, Self::T) -> V>(self, f: F) -> Vec<V> {
+        self.map_indexed(f)
+    }
 
-            // drop the remaining elements of the left vec
-            std::ptr::drop_in_place(std::slice::from_raw_parts_mut(
-                vec.left.ptr,
-                vec.left.len - vec.init_len,
-            ));
-        }
+    /// Zip a vector with it's own indices `0..len` and combine them, without
+    /// allocating a vector of indices, will try and reuse the allocation in the
+    /// same cases as [`map`](VecExt::map)
+    ///
+    /// The mapping function can be fallible, and on early return, it will drop all previous values,
+    /// and the rest of the input vector. The error will be returned as a `Result`
+    fn try_zip_with_index<V, R: Try<Ok = V>, F: FnMut(usize, Self::T) -> R>(
+        self,
+        f: F,
+    ) -> Result<Vec<V>, R::Error> {
+        self.try_map_indexed(f)
+    }
+
+    /// Map a vector to another vector, keeping only the values where `f` returns `Some`,
+    /// will try and reuse the allocation if the allocation layouts of the two types match, i.e. if
+    /// `std::alloc::Layout::<T>::new() == std::alloc::Layout::<U>::new()`
+    /// then the allocation will be reused
+    fn filter_map<U, F: FnMut(Self::T) -> Option<U>>(self, mut f: F) -> Vec<U> {
+        use core::convert::Infallible;
 
-        Ok(output)
+        match self.try_filter_map(move |x| Ok::<_, Infallible>(f(x))) {
+            Ok(x) => x,
+            Err(x) => match x {},
+        }
     }
-}
 
-impl<T, U, V> Drop for ZipWithIter<T, U, V> {
-    fn drop(&mut self) {
+    /// Map a vector to another vector, keeping only the values where `f` returns `Some`,
+    /// will try and reuse the allocation if the allocation layouts of the two types match, i.e. if
+    /// `std::alloc::Layout::<T>::new() == std::alloc::Layout::<U>::new()`
+    /// then the allocation will be reused
+    ///
+    /// The mapping function can be fallible, and on early return, it will drop all previous values,
+    /// and the rest of the input vector. The error will be returned as a `Result`
+    fn try_filter_map<U, R: Try<Ok = Option<U>>, F: FnMut(Self::T) -> R>(
+        self,
+        f: F,
+    ) -> Result<Vec<U>, R::Error>;
+
+    /// Map a vector to another vector, until `f` returns `None`, then drop the rest of the
+    /// input, will try and reuse the allocation if the allocation layouts of the two types match, i.e. if
+    /// `std::alloc::Layout::<T>::new() == std::alloc::Layout::<U>::new()`
+    /// then the allocation will be reused, and the output will keep the capacity of the input
+    fn map_while<U, F: FnMut(Self::T) -> Option<U>>(self, f: F) -> Vec<U>;
+
+    /// Map a vector to another vector, catching any panic in `f`, will try and reuse
+    /// the allocation in the same cases as [`map_while`](VecExt::map_while)
+    ///
+    /// If `f` panics, the values that were already mapped are returned along with the
+    /// panic payload, and the rest of the input is dropped
+    ///
+    /// This is only available with the `std` feature
+    #[cfg(feature = "std")]
+    #[allow(clippy::type_complexity)]
+    fn map_catch_unwind<U, F: FnMut(Self::T) -> U + std::panic::UnwindSafe>(
+        self,
+        mut f: F,
+    ) -> Result<Vec<U>, (Vec<U>, Box<dyn core::any::Any + Send>)> {
+        use std::panic::{catch_unwind, AssertUnwindSafe};
+
+        let mut payload = None;
+
+        let vec = self.map_while(|x| match catch_unwind(AssertUnwindSafe(|| f(x))) {
+            Ok(value) => Some(value),
+            Err(err) => {
+                payload = Some(err);
+                None
+            }
+        });
+
+        match payload {
+            None => Ok(vec),
+            Some(payload) => Err((vec, payload)),
+        }
+    }
+
+    /// Map each element of a vector to an iterator, and flatten the results into a single vector,
+    /// will try and reuse the allocation if the allocation layouts of the two types match, i.e. if
+    /// `std::alloc::Layout::<T>::new() == std::alloc::Layout::<U>::new()`
+    ///
+    /// The output is written into the input allocation for as long as it fits behind the
+    /// unread input. If the output catches up to the unread input, then the unread input
+    /// is moved into a new allocation, and the output grows like a normal `Vec` from then on
+    fn flat_map<U, I: IntoIterator<Item = U>, F: FnMut(Self::T) -> I>(self, f: F) -> Vec<U>;
+
+    /// Map each element of a vector to zero or more values, which `f` pushes into the
+    /// vector it's given, will try and reuse the allocation in the same cases as
+    /// [`flat_map`](VecExt::flat_map)
+    ///
+    /// The vector that `f` is given is always empty when `f` is called, and the values
+    /// that `f` pushed are moved into the output after it returns. Each input is read
+    /// before `f` is called on it, and the output is only written into slots that were
+    /// already read, so pushing can never write over the input that hasn't been read yet.
+    /// The vector that is given to `f` is reused between calls, so if most elements map
+    /// to a single value, it only allocates once
+    fn map_expanding<U, F: FnMut(Self::T, &mut Vec<U>)>(self, f: F) -> Vec<U>;
+
+    /// Map each element of a vector to zero or more values in two passes, first `count`
+    /// is called on every element to measure the length of the output, then `fill` pushes
+    /// each element's values in the same way as [`map_expanding`](VecExt::map_expanding)
+    ///
+    /// If the layouts of `T` and `U` match, then the input allocation is grown once up front,
+    /// and the input is moved to the end of it, so that the output never catches up to the
+    /// values that haven't been read yet, and the allocation is reused. Otherwise a new
+    /// allocation of exactly the counted length is made, so the output never reallocates
+    /// while it is being filled
+    ///
+    /// `count` must return the number of values that `fill` will push for that element.
+    /// If the counts are wrong the output is still correct, but it may reallocate, and
+    /// this will panic with debug assertions enabled
+    fn map_two_phase<U, C: FnMut(&Self::T) -> usize, F: FnMut(Self::T, &mut Vec<U>)>(
+        self,
+        count: C,
+        fill: F,
+    ) -> Vec<U>;
+
+    /// Map a vector of arrays to another vector of arrays of the same length, passing each
+    /// whole array to `f`, will try and reuse the allocation in the same cases as
+    /// [`map`](VecExt::map). So if the layouts of `T` and `U` match, then the layouts
+    /// of `[T; N]` and `[U; N]` also match, and the allocation will be reused
+    fn map_arrays<const N: usize, T, U, F: FnMut([T; N]) -> [U; N]>(self, f: F) -> Vec<[U; N]>
+    where
+        Self: VecExt<T = [T; N]>,
+    {
+        self.map(f)
+    }
+
+    /// Map a vector in groups of `N` elements, each group is passed to `f` as an owned array
+    ///
+    /// The output has `len / N` elements, and if `len % N != 0`, the trailing elements
+    /// that don't fill a whole group are returned as the second vector
+    ///
+    /// The allocation will be reused if `T` and `U` have the same alignment and
+    /// `size_of::<U>() <= N * size_of::<T>()`, because each output is written behind
+    /// the group that was just read
+    ///
+    /// # Panic
+    ///
+    /// if `N == 0`
+    fn chunks_map<const N: usize, U, F: FnMut([Self::T; N]) -> U>(
+        self,
+        f: F,
+    ) -> (Vec<U>, Vec<Self::T>);
+
+    /// Map each overlapping window of `window` elements of a vector, this produces
+    /// `len - window + 1` outputs, or an empty vector if `window > len`
+    ///
+    /// The allocation will be reused if `T` and `U` have the same alignment and
+    /// `size_of::<U>() <= size_of::<T>()`, because after each window the first element
+    /// of that window is dropped, and the output is written in it's place
+    ///
+    /// # Panic
+    ///
+    /// if `window == 0`
+    fn windows_map<U, F: FnMut(&[Self::T]) -> U>(self, window: usize, f: F) -> Vec<U>;
+
+    /// Lazily map a vector, this returns an iterator that yields the mapped values
+    ///
+    /// Iterating doesn't reuse the allocation, because the values are handed out
+    /// one at a time, but [`MapIntoIter::into_vec`] will map the rest of the values and
+    /// reuse the allocation in the same cases as [`map`](VecExt::map)
+    ///
+    /// The iterator is double ended, so values can be taken from both ends,
+    /// and `into_vec` will map the values that are left in the middle
+    ///
+    /// Collecting the iterator, i.e. `vec.into_map_iter(f).collect::<Vec<_>>()`, always
+    /// allocates a new vector, because `collect` can't see the input allocation. Use
+    /// `into_vec` instead to reuse it
+    fn into_map_iter<U, F: FnMut(Self::T) -> U>(self, f: F) -> MapIntoIter<Self::T, U, F>;
+
+    /// Transform the elements of a vector, and only keep the ones where `f` returns `Some`.
+    /// Because the input and output types are the same, this always reuses the allocation
+    fn retain_map<F: FnMut(Self::T) -> Option<Self::T>>(self, f: F) -> Vec<Self::T> {
+        self.filter_map(f)
+    }
+
+    /// Map a vector to another vector, and remove consecutive duplicates, will try and reuse
+    /// the allocation in the same cases as [`filter_map`](VecExt::filter_map)
+    ///
+    /// Each element is mapped with `f` first, then `key` is called on the output, and the output
+    /// is dropped if it's key is equal to the key of the last output that was kept
+    fn dedup_map<U, F: FnMut(Self::T) -> U, K: PartialEq, G: FnMut(&U) -> K>(
+        self,
+        mut f: F,
+        mut key: G,
+    ) -> Vec<U> {
+        let mut last = None;
+
+        self.filter_map(|x| {
+            let value = f(x);
+            let value_key = key(&value);
+
+            if last.as_ref() == Some(&value_key) {
+                None
+            } else {
+                last = Some(value_key);
+                Some(value)
+            }
+        })
+    }
+
+    /// Split a vector into two vectors in a single pass, depending on whether `f` returns
+    /// `Either::Left` or `Either::Right`. The allocation will be reused for the left vector
+    /// if the allocation layouts of `T` and `L` match, the right vector will be allocated
+    fn partition_map<L, R, F: FnMut(Self::T) -> Either<L, R>>(self, mut f: F) -> (Vec<L>, Vec<R>) {
+        let mut right = Vec::new();
+
+        // if `f` panics, `filter_map` cleans up the input and the left vector,
+        // and `right` will be dropped normally
+        let left = self.filter_map(|x| match f(x) {
+            Either::Left(x) => Some(x),
+            Either::Right(x) => {
+                right.push(x);
+                None
+            }
+        });
+
+        (left, right)
+    }
+
+    /// Split a vector into two vectors in a single pass, every element is mapped with `g`,
+    /// and goes into the first vector if `pred` returns true, or the second vector otherwise.
+    /// The allocation will be reused for the first vector if the allocation layouts of `T`
+    /// and `U` match, the second vector will be allocated
+    fn split_map<U, P: FnMut(&Self::T) -> bool, G: FnMut(Self::T) -> U>(
+        self,
+        mut pred: P,
+        mut g: G,
+    ) -> (Vec<U>, Vec<U>) {
+        let mut rest = Vec::new();
+
+        // if `pred` or `g` panic, `filter_map` cleans up the input and the first vector,
+        // and `rest` will be dropped normally
+        let vec = self.filter_map(|x| {
+            if pred(&x) {
+                Some(g(x))
+            } else {
+                rest.push(g(x));
+                None
+            }
+        });
+
+        (vec, rest)
+    }
+
+    /// Group runs of consecutive elements with equal keys, and combine each run into a
+    /// single value with `agg`. The output has one value for each run, in order.
+    /// The allocation will be reused in the same cases as [`filter_map`](VecExt::filter_map),
+    /// because there are never more runs than elements
+    ///
+    /// Each run is handed to `agg` as a new vector. If the input is empty, then there are
+    /// no runs, so neither `key` nor `agg` are called, and the output is empty
+    fn group_map<K: PartialEq, U, KeyF: FnMut(&Self::T) -> K, AggF: FnMut(Vec<Self::T>) -> U>(
+        self,
+        mut key: KeyF,
+        mut agg: AggF,
+    ) -> Vec<U> {
+        let mut run = Vec::new();
+        let mut run_key = None;
+
+        // each run is only combined once the next run starts, so the last run is left over
+        //
+        // if `key` or `agg` panic, `filter_map` cleans up the input and the output,
+        // and `run` will be dropped normally
+        let mut vec = self.filter_map(|x| {
+            let key = key(&x);
+
+            let value = match run_key {
+                Some(ref run_key) if *run_key != key => Some(agg(core::mem::take(&mut run))),
+                _ => None,
+            };
+
+            run_key = Some(key);
+            run.push(x);
+            value
+        });
+
+        // there were fewer outputs than inputs, so if the allocation
+        // was reused, then this won't allocate
+        if !run.is_empty() {
+            vec.push(agg(run));
+        }
+
+        vec
+    }
+
+    /// Zip a vector to another vector and combine them, the result will be returned,
+    /// the allocation will be reused if possible, the larger allocation of the input vectors
+    /// will be used if all of `T`, `U`, and `V` have the same allocation layouts.
+    ///
+    /// An input's allocation can also be reused if it has the same alignment as `V`, in
+    /// the same cases as [`map`](VecExt::map), then the larger allocation in bytes is used.
+    /// If `V` is larger than both inputs and neither has enough spare capacity, then the
+    /// larger input with the same alignment as `V` is grown with `realloc` and reused
+    fn zip_with<U, V, F: FnMut(Self::T, U) -> V>(self, other: Vec<U>, mut f: F) -> Vec<V> {
+        use core::convert::Infallible;
+
+        match self.try_zip_with(other, move |x, y| Ok::<_, Infallible>(f(x, y))) {
+            Ok(x) => x,
+            Err(x) => match x {},
+        }
+    }
+
+    /// Zip a vector with an iterator and combine them, will try and reuse the allocation
+    /// of the vector in the same cases as [`map_while`](VecExt::map_while)
+    ///
+    /// This stops as soon as either the vector or the iterator runs out, the rest of the
+    /// vector is dropped, and the rest of the iterator is left unread
+    fn zip_with_iter<I: IntoIterator, V, F: FnMut(Self::T, I::Item) -> V>(
+        self,
+        other: I,
+        mut f: F,
+    ) -> Vec<V> {
+        let mut other = other.into_iter();
+
+        self.map_while(move |x| other.next().map(|y| f(x, y)))
+    }
+
+    /// Zip a vector with an array and combine them, will try and reuse the allocation
+    /// of the vector in the same cases as [`map_while`](VecExt::map_while)
+    ///
+    /// The output will have the length `min(self.len(), N)`, and the leftover
+    /// elements of the vector or the array are dropped
+    fn zip_with_array<U, V, const N: usize, F: FnMut(Self::T, U) -> V>(
+        self,
+        other: [U; N],
+        f: F,
+    ) -> Vec<V> {
+        self.zip_with_iter(other, f)
+    }
+
+    /// Zip a vector to another vector and combine them, the result will be returned,
+    /// the allocation will be reused if possible, the larger allocation of the input vectors
+    /// will be used if all of `T`, `U`, and `V` have the same allocation layouts.
+    ///
+    /// An input's allocation can also be reused if it has the same alignment as `V`, in
+    /// the same cases as [`map`](VecExt::map), then the larger allocation in bytes is used.
+    /// If `V` is larger than both inputs and neither has enough spare capacity, then the
+    /// larger input with the same alignment as `V` is grown with `realloc` and reused
+    ///
+    /// The mapping function can be fallible, and on early return, it will drop all previous values,
+    /// and the rest of the input vectors. The error will be returned as a `Result`
+    fn try_zip_with<U, V, R: Try<Ok = V>, F: FnMut(Self::T, U) -> R>(
+        self,
+        other: Vec<U>,
+        f: F,
+    ) -> Result<Vec<V>, R::Error>;
+
+    /// Zip a vector to another vector of the same length and combine them, in the same way as
+    /// [`zip_with`](VecExt::zip_with)
+    ///
+    /// # Panic
+    ///
+    /// if the two vectors have different lengths, then this function will panic
+    fn zip_eq_with<U, V, F: FnMut(Self::T, U) -> V>(self, other: Vec<U>, mut f: F) -> Vec<V> {
+        use core::convert::Infallible;
+
+        match self.try_zip_eq_with(other, move |x, y| Ok::<_, Infallible>(f(x, y))) {
+            Ok(x) => x,
+            Err(x) => match x {},
+        }
+    }
+
+    /// Zip a vector to another vector of the same length and combine them, in the same way as
+    /// [`try_zip_with`](VecExt::try_zip_with)
+    ///
+    /// # Panic
+    ///
+    /// if the two vectors have different lengths, then this function will panic
+    fn try_zip_eq_with<U, V, R: Try<Ok = V>, F: FnMut(Self::T, U) -> R>(
+        self,
+        other: Vec<U>,
+        f: F,
+    ) -> Result<Vec<V>, R::Error>;
+
+    /// Zip a vector to another vector and combine them, in the same way as
+    /// [`zip_with`](VecExt::zip_with), after checking that the tags of the
+    /// two vectors match, to catch zipping vectors that aren't related
+    ///
+    /// This check is only a `debug_assert`, so in release builds the tags are ignored
+    ///
+    /// # Panic
+    ///
+    /// if debug assertions are enabled, and `tag_self != tag_other`
+    fn zip_with_tagged<U, V, F: FnMut(Self::T, U) -> V>(
+        self,
+        tag_self: u64,
+        other: Vec<U>,
+        tag_other: u64,
+        f: F,
+    ) -> Vec<V> {
+        debug_assert_eq!(
+            tag_self, tag_other,
+            "Tried to zip vectors with different tags"
+        );
+
+        self.zip_with(other, f)
+    }
+
+    /// Zip a vector to another vector and combine them, in the same way as
+    /// [`zip_with`](VecExt::zip_with), but instead of dropping the leftover elements of
+    /// the longer vector, they are returned in the `Remainder`
+    fn zip_with_remainder<U, V, F: FnMut(Self::T, U) -> V>(
+        self,
+        other: Vec<U>,
+        f: F,
+    ) -> (Vec<V>, Remainder<Self::T, U>);
+
+    /// Zip a vector to another vector and combine them, in the same way as
+    /// [`zip_with`](VecExt::zip_with), but stop early once `f` returns `true`
+    ///
+    /// The value that `f` returned along with `true` is the last value in the output,
+    /// and the rest of both vectors are dropped without being passed to `f`
+    fn zip_with_until<U, V, F: FnMut(Self::T, U) -> (V, bool)>(self, other: Vec<U>, f: F)
+        -> Vec<V>;
+
+    /// Zip a vector to another vector and combine them, in the same way as
+    /// [`zip_with`](VecExt::zip_with), but instead of stopping at the end of the shorter
+    /// vector, the leftover elements of the longer vector are passed to `f` on their own
+    ///
+    /// The output will have the length of the longer vector. If the output reuses
+    /// an allocation that can't fit all of it, then the output will grow
+    fn zip_longest_with<U, V, F: FnMut(EitherOrBoth<Self::T, U>) -> V>(
+        self,
+        other: Vec<U>,
+        mut f: F,
+    ) -> Vec<V> {
+        let (mut vec, remainder) =
+            self.zip_with_remainder(other, |x, y| f(EitherOrBoth::Both(x, y)));
+
+        // if `f` panics, `vec` and the rest of the remainder will be dropped normally
+        match remainder {
+            Remainder::Left(rest) => vec.extend(rest.into_iter().map(|x| f(EitherOrBoth::Left(x)))),
+            Remainder::Right(rest) => {
+                vec.extend(rest.into_iter().map(|y| f(EitherOrBoth::Right(y))))
+            }
+            Remainder::Neither => (),
+        }
+
+        vec
+    }
+
+    /// Zip a vector of keys to a vector of values, and collect them into a `HashMap`.
+    /// If a key shows up more than once, then the later value replaces the earlier one
+    ///
+    /// This stops at the end of the shorter vector, and the leftover elements of
+    /// the longer vector are dropped. The map is a new allocation, so this never
+    /// reuses the allocations of the vectors
+    ///
+    /// This is only available with the `std` feature
+    #[cfg(feature = "std")]
+    fn zip_into_map<U>(self, values: Vec<U>) -> std::collections::HashMap<Self::T, U>
+    where
+        Self::T: Eq + core::hash::Hash,
+    {
+        self.zip_into_map_with(values, |_, value| value)
+    }
+
+    /// Zip a vector of keys to a vector of values, and collect them into a `HashMap`,
+    /// in the same way as [`zip_into_map`](VecExt::zip_into_map). If a key shows up more
+    /// than once, then the earlier and later values are combined with `combine`
+    ///
+    /// If `combine` panics, then the map and the rest of both vectors are dropped
+    ///
+    /// This is only available with the `std` feature
+    #[cfg(feature = "std")]
+    fn zip_into_map_with<U, F: FnMut(U, U) -> U>(
+        self,
+        values: Vec<U>,
+        combine: F,
+    ) -> std::collections::HashMap<Self::T, U>
+    where
+        Self::T: Eq + core::hash::Hash;
+
+    /// Interleave the elements of two vectors, starting with `self`, the leftover elements
+    /// of the longer vector are put at the end. The larger allocation of the two vectors
+    /// will be reused, and it will grow if it can't fit both vectors
+    fn interleave(self, other: Vec<Self::T>) -> Vec<Self::T>;
+
+    /// Zip a vector to another vector and combine them, in the same way as
+    /// [`zip_with`](VecExt::zip_with), but instead of stopping at the end of the shorter
+    /// vector, the shorter vector is padded with `Default::default()`
+    ///
+    /// The output will have the length of the longer vector. The shorter vector is padded
+    /// before zipping, so if it's allocation is reused, it may have to grow to fit the output
+    fn zip_with_default<U, V, F: FnMut(Self::T, U) -> V>(self, other: Vec<U>, f: F) -> Vec<V>
+    where
+        Self::T: Default,
+        U: Default;
+
+    /// Zip a vector with two other vectors and combine them, the result will be returned,
+    /// the allocation will be reused if possible, the largest allocation of the input vectors
+    /// that has the same allocation layout as `V` will be used.
+    ///
+    /// The output will have the length of the shortest input vector
+    fn zip3_with<B, C, V, F: FnMut(Self::T, B, C) -> V>(
+        self,
+        b: Vec<B>,
+        c: Vec<C>,
+        mut f: F,
+    ) -> Vec<V> {
+        use core::convert::Infallible;
+
+        match self.try_zip3_with(b, c, move |x, y, z| Ok::<_, Infallible>(f(x, y, z))) {
+            Ok(x) => x,
+            Err(x) => match x {},
+        }
+    }
+
+    /// Zip a vector with two other vectors and combine them, the result will be returned,
+    /// the allocation will be reused if possible, the largest allocation of the input vectors
+    /// that has the same allocation layout as `V` will be used.
+    ///
+    /// The output will have the length of the shortest input vector
+    ///
+    /// The mapping function can be fallible, and on early return, it will drop all previous values,
+    /// and the rest of the input vectors. The error will be returned as a `Result`
+    fn try_zip3_with<B, C, V, R: Try<Ok = V>, F: FnMut(Self::T, B, C) -> R>(
+        self,
+        b: Vec<B>,
+        c: Vec<C>,
+        f: F,
+    ) -> Result<Vec<V>, R::Error>;
+
+    /// Map a vector to another vector in parallel using `rayon`, will try and reuse the
+    /// allocation if the allocation layouts of the two types match, i.e. if
+    /// `std::alloc::Layout::<T>::new() == std::alloc::Layout::<U>::new()`
+    /// then the allocation will be reused
+    ///
+    /// The output will be in the same order as the input
+    ///
+    /// This is only available with the `rayon` feature
+    #[cfg(feature = "rayon")]
+    fn par_map<U, F>(self, f: F) -> Vec<U>
+    where
+        F: Fn(Self::T) -> U + Sync + Send,
+        U: Send,
+        Self::T: Send;
+
+    /// Map a vector to another vector in parallel in the given `rayon` thread pool,
+    /// in the same way as [`par_map`](VecExt::par_map)
+    ///
+    /// The work is split into about 4 chunks per thread in `pool`, so that
+    /// rayon has some room to balance the work between the threads
+    ///
+    /// This is only available with the `rayon` feature
+    #[cfg(feature = "rayon")]
+    fn par_map_in_pool<U, F>(self, pool: &rayon::ThreadPool, f: F) -> Vec<U>
+    where
+        F: Fn(Self::T) -> U + Sync + Send,
+        U: Send,
+        Self::T: Send;
+
+    /// Map a vector to another vector `LANES` elements at a time, with `core::simd`,
+    /// will try and reuse the allocation if the allocation layouts of the two types match,
+    /// i.e. if `std::alloc::Layout::<T>::new() == std::alloc::Layout::<U>::new()`
+    /// then the allocation will be reused
+    ///
+    /// If the length isn't a multiple of `LANES`, then the last chunk is padded with
+    /// `T::default()` before it's passed to `f`, and the padding lanes of the output
+    /// are ignored. If `f` panics, then the allocation is freed
+    ///
+    /// This is only available with the `portable_simd` feature, which requires nightly
+    #[cfg(feature = "portable_simd")]
+    fn map_simd<U, const LANES: usize, F>(self, f: F) -> Vec<U>
+    where
+        F: FnMut(core::simd::Simd<Self::T, LANES>) -> core::simd::Simd<U, LANES>,
+        Self::T: core::simd::SimdElement + Default,
+        U: core::simd::SimdElement;
+
+    /// Zip a vector to another vector and combine them in parallel, with [`rayon`](https://docs.rs/rayon),
+    /// the allocation will be reused in the same cases as [`zip_with`](VecExt::zip_with)
+    ///
+    /// The leftover elements of the longer vector are dropped on the calling thread
+    /// before the work is split up
+    ///
+    /// This is only available with the `rayon` feature
+    #[cfg(feature = "rayon")]
+    fn par_zip_with<U, V, F>(self, other: Vec<U>, f: F) -> Vec<V>
+    where
+        F: Fn(Self::T, U) -> V + Sync + Send,
+        U: Send,
+        V: Send,
+        Self::T: Send;
+
+    /// Map a vector into a vector that is allocated in a [`bumpalo`](https://docs.rs/bumpalo) arena
+    ///
+    /// The output is always allocated in `bump`, so the allocation of the input is
+    /// never reused, instead it's released back to the global allocator once all
+    /// of the values are mapped, even though the output lives on in the arena.
+    /// If `f` panics, then the rest of the input and all of the values that were
+    /// already mapped are dropped, in the same way as [`map`](VecExt::map)
+    #[cfg(feature = "bumpalo")]
+    fn map_in_bump<'b, U, F: FnMut(Self::T) -> U>(
+        self,
+        bump: &'b bumpalo::Bump,
+        f: F,
+    ) -> bumpalo::collections::Vec<'b, U>;
+
+    /// Drops all of the values in the vector and
+    /// create a new vector from it if the layouts are compatible
+    ///
+    /// The layouts are compatible if `T` and `U` have the same alignment, and neither is
+    /// zero-sized. Then the new vector will have a capacity of
+    /// `cap * size_of::<T>() / size_of::<U>()`, and if that doesn't cover the whole
+    /// allocation, the allocation is shrunk in place with `realloc`
+    ///
+    /// if layouts are not compatible, or the allocation can't fit a single `U`,
+    /// then return `Vec::new()`
+    fn drop_and_reuse<U>(self) -> Vec<U>;
+
+    /// Drops all of the values in the vector, and create a new empty vector from it if
+    /// the allocation layouts of the two types match, in the same cases as [`map`](VecExt::map)
+    ///
+    /// This gives the same vector as mapping the cleared vector, but it doesn't take a
+    /// mapping function at all, so there is nothing that could be called. If the allocation
+    /// is reused, then the new vector has the same capacity, and is never reallocated.
+    /// Otherwise, this returns `Vec::new()`, see [`drop_and_reuse`](VecExt::drop_and_reuse)
+    /// to also reuse allocations of types with the same alignment
+    fn clear_and_reuse<U>(self) -> Vec<U>;
+
+    /// Wrap each element of the vector in the `#[repr(transparent)]` newtype `W`,
+    /// this always reuses the allocation, and doesn't touch any of the elements
+    fn wrap<W: TransparentWrapper<Self::T>>(self) -> Vec<W>;
+
+    /// Unwrap each element of a vector of `#[repr(transparent)]` newtypes, this
+    /// always reuses the allocation, and doesn't touch any of the elements
+    fn unwrap_newtype<I>(self) -> Vec<I>
+    where
+        Self::T: TransparentWrapper<I>;
+
+    /// Fold every element of the vector into an accumulator, consuming the vector
+    ///
+    /// If `f` panics, then the rest of the elements are dropped, and the allocation is freed
+    fn consume_fold<B, F: FnMut(B, Self::T) -> B>(self, init: B, f: F) -> B;
+
+    /// Map each element of the vector, and send the results through `tx` one at a time,
+    /// without collecting them into an output vector. This blocks whenever the channel
+    /// is full, in the same way as `SyncSender::send`
+    ///
+    /// If the receiver was dropped, then this stops and returns the value that couldn't
+    /// be sent, and the rest of the input is dropped. The allocation can't be freed a
+    /// piece at a time, so it's freed once the input is consumed, or if `f` panics
+    ///
+    /// This is only available with the `std` feature
+    #[cfg(feature = "std")]
+    fn map_send<U, F: FnMut(Self::T) -> U>(
+        self,
+        tx: std::sync::mpsc::SyncSender<U>,
+        f: F,
+    ) -> Result<(), std::sync::mpsc::SendError<U>>;
+
+    /// Split the vector into chunks of `N` elements, and pass each chunk to `f`, which
+    /// can change it in place. Each chunk is dropped right after `f` is done with it, so
+    /// the values don't outlive their chunk. If the length isn't a multiple of `N`, then
+    /// the remainder is passed last, as a shorter chunk
+    ///
+    /// The allocation can't be freed a piece at a time, so it is freed all at once
+    /// after the last chunk. If `f` panics, then the rest of the elements are dropped,
+    /// and the allocation is freed
+    ///
+    /// # Panics
+    ///
+    /// If `N` is zero
+    fn map_chunks_exact<const N: usize, F: FnMut(&mut [Self::T])>(self, f: F);
+
+    /// Reduce the elements of the vector into a single value, starting with the first
+    /// element, and folding the rest of the elements into it. Returns `Ok(None)` if the
+    /// vector is empty
+    ///
+    /// The folding function can be fallible, and on early return, it will drop the rest
+    /// of the input vector. The error will be returned as a `Result`
+    fn try_reduce<R: Try<Ok = Self::T>, F: FnMut(Self::T, Self::T) -> R>(
+        self,
+        f: F,
+    ) -> Result<Option<Self::T>, R::Error>;
+
+    /// Apply a function to every element of the vector in place, this
+    /// doesn't allocate and doesn't move any of the elements
+    ///
+    /// If `f` panics, then all of the elements before the one that
+    /// caused the panic will have already been updated, and the rest
+    /// of the elements will be left untouched
+    fn map_in_place<F: FnMut(&mut Self::T)>(&mut self, f: F);
+
+    /// Zip the vector with another vector, and combine each element of `other` into
+    /// the element of the vector at the same position, this doesn't allocate
+    ///
+    /// If `other` is shorter, then the vector is truncated to the length of `other`,
+    /// and if `other` is longer, then the rest of `other` is dropped
+    ///
+    /// If `f` panics, then all of the elements before the one that
+    /// caused the panic will have already been updated, and the rest
+    /// of the elements will be left untouched
+    fn zip_with_mut<U, F: FnMut(&mut Self::T, U)>(&mut self, other: Vec<U>, f: F);
+
+    /// Borrow the spare capacity of the vector as a scratch buffer of `U`s,
+    /// this doesn't allocate and doesn't touch any of the elements of the vector
+    ///
+    /// The scratch buffer starts out empty, and has a capacity of
+    /// `(cap - len) * size_of::<T>() / size_of::<U>()`. It can't reallocate,
+    /// so pushing past it's capacity will panic, see [`ScratchVec`].
+    /// Any values left in the buffer are dropped before this returns
+    ///
+    /// If the alignment of `U` is larger than the alignment of `T`,
+    /// or if the spare capacity can't fit a single `U`, then the scratch
+    /// buffer will have a capacity of zero
+    fn with_scratch<U, R, F: FnOnce(&mut ScratchVec<'_, U>) -> R>(&mut self, f: F) -> R;
+
+    /// Map the elements of a borrowed vector in place, changing their type to `U`
+    ///
+    /// The type of `self` can't change, so the mapped values are handed back in a
+    /// [`ReplaceMap`], which derefs to a `Vec<U>` that owns the allocation of `self`.
+    /// While it's alive, `self` is left empty. Once it's dropped, the `U`s are dropped
+    /// and `self` gets the allocation back, so it keeps it's capacity.
+    ///
+    /// If `f` panics, then `self` is left empty, and the allocation is freed
+    ///
+    /// # Panic
+    ///
+    /// if `std::alloc::Layout::<T>::new() != std::alloc::Layout::<U>::new()`,
+    /// because then the allocation couldn't be mapped in place
+    fn replace_map<U, F: FnMut(Self::T) -> U>(&mut self, f: F) -> ReplaceMap<'_, Self::T, U>;
+
+    /// Take the elements out of a borrowed vector, and map them into a new vector,
+    /// will try and reuse the allocation in the same cases as [`map`](VecExt::map)
+    ///
+    /// The allocation of `self` moves into the output, so `self` is always left as
+    /// `Vec::new()`, with a capacity of zero. If the allocation can't be reused,
+    /// then it's freed once all of the elements are mapped.
+    ///
+    /// If `f` panics, then `self` is still left empty, and the allocation is freed
+    fn take_map<U, F: FnMut(Self::T) -> U>(&mut self, f: F) -> Vec<U>;
+}
+
+impl<T> VecExt for Vec<T> {
+    type T = T;
+
+    fn try_map<U, R: Try<Ok = U>, F: FnMut(Self::T) -> R>(
+        self,
+        mut f: F,
+    ) -> Result<Vec<U>, R::Error> {
+        try_map_indexed(self, move |_, x| f(x))
+    }
+
+    fn try_map_resumable<U, R: Try<Ok = U>, F: FnMut(Self::T) -> R>(
+        self,
+        f: F,
+    ) -> Result<Vec<U>, (Vec<U>, R::Error, Vec<T>)> {
+        try_map_resumable(self, f)
+    }
+
+    fn map_with_len<U, F: FnMut(usize, usize, Self::T) -> U>(self, mut f: F) -> Vec<U> {
+        let len = self.len();
+
+        self.map_indexed(move |i, x| f(i, len, x))
+    }
+
+    fn unzip_with<A, B, F: FnMut(Self::T) -> (A, B)>(self, mut f: F) -> (Vec<A>, Vec<B>) {
+        // if `f` panics, `map` cleans up the input and it's own output,
+        // and `other` will be dropped normally
+        if Layout::new::<T>() != Layout::new::<A>() && Layout::new::<T>() == Layout::new::<B>() {
+            let mut other = Vec::with_capacity(self.len());
+            let right = self.map(|x| {
+                let (a, b) = f(x);
+                other.push(a);
+                b
+            });
+
+            (other, right)
+        } else {
+            let mut other = Vec::with_capacity(self.len());
+            let left = self.map(|x| {
+                let (a, b) = f(x);
+                other.push(b);
+                a
+            });
+
+            (left, other)
+        }
+    }
+
+    fn map_split2<A, B, F: FnMut(Self::T) -> (A, B)>(self, mut f: F) -> (Vec<A>, Vec<B>) {
+        // `other` gets exactly enough capacity for every element, so pushing never grows it
+        //
+        // if `f` panics, `map_with_capacity` cleans up the input and it's own output,
+        // and `other` will be dropped normally
+        if Layout::new::<T>() != Layout::new::<A>() && Layout::new::<T>() == Layout::new::<B>() {
+            let mut other = Vec::with_capacity(self.len());
+            let right = self.map_with_capacity(|x| {
+                let (a, b) = f(x);
+                other.push(a);
+                b
+            });
+
+            (other, right)
+        } else {
+            let mut other = Vec::with_capacity(self.len());
+            let left = self.map_with_capacity(|x| {
+                let (a, b) = f(x);
+                other.push(b);
+                a
+            });
+
+            (left, other)
+        }
+    }
+
+    fn try_map_alloc<U, F: FnMut(Self::T) -> U>(self, f: F) -> Result<Vec<U>, TryReserveError> {
+        map_or_else(self, f, Ok, |vec, f| {
+            let mut out = Vec::new();
+            out.try_reserve_exact(vec.len())?;
+
+            // `out` already has enough capacity, so this won't allocate
+            out.extend(vec.into_iter().map(f));
+
+            Ok(out)
+        })
+    }
+
+    unsafe fn map_unchecked<U, F: FnMut(Self::T) -> U>(self, mut f: F) -> Vec<U> {
+        use core::convert::Infallible;
+
+        debug_assert_eq!(Layout::new::<T>(), Layout::new::<U>());
+
+        // these are known at compile time, so they don't add a branch
+        if !can_reuse() || is_zst::<U>() {
+            return self.map(f);
+        }
+
+        let iter = MapIter {
+            init_len: 0,
+            data: Input::from(self),
+            drop: PhantomData,
+        };
+
+        match iter.try_into_vec(move |_, x| Ok::<_, Infallible>(f(x))) {
+            Ok(x) => x,
+            Err(x) => match x {},
+        }
+    }
+
+    fn map_with_capacity<U, F: FnMut(Self::T) -> U>(self, f: F) -> Vec<U> {
+        map_or_else(self, f, core::convert::identity, |vec, f| {
+            let mut out = Vec::with_capacity(vec.len());
+
+            // `out` already has enough capacity, so this won't allocate
+            out.extend(vec.into_iter().map(f));
+
+            out
+        })
+    }
+
+    fn map_shrink<U, F: FnMut(Self::T) -> U>(self, mut f: F) -> Vec<U> {
+        use core::convert::Infallible;
+
+        let vec = if self.capacity() == 0 {
+            Err(self)
+        } else {
+            MapIter::new(self)
+        };
+
+        match vec {
+            Ok(iter) => match iter.try_into_vec_shrunk(|_, x| Ok::<_, Infallible>(f(x))) {
+                Ok(x) => x,
+                Err(x) => match x {},
+            },
+            Err(vec) => {
+                let mut vec = vec.into_iter().map(f).collect::<Vec<_>>();
+                vec.shrink_to_fit();
+                vec
+            }
+        }
+    }
+
+    fn map_reporting<U, F: FnMut(Self::T) -> U>(self, f: F) -> (Vec<U>, Reused) {
+        map_or_else(
+            self,
+            f,
+            |vec| (vec, Reused::Buffer),
+            |vec, f| (vec.into_iter().map(f).collect(), Reused::Fresh),
+        )
+    }
+
+    fn map_pooled<U, F: FnMut(Self::T) -> U>(self, pool: &mut VecPool<U>, f: F) -> Vec<U> {
+        map_or_else(self, f, core::convert::identity, |vec, f| {
+            // there are no elements to map, so don't take a vector from the pool
+            if vec.capacity() == 0 {
+                return Vec::new();
+            }
+
+            let mut out = pool.take();
+            vec.map_into(&mut out, f);
+            out
+        })
+    }
+
+    fn map_into<U, F: FnMut(Self::T) -> U>(self, out: &mut Vec<U>, f: F) {
+        // `vec::IntoIter` drops the remaining inputs if `f` panics, and `extend`
+        // keeps the length of `out` up to date as it writes into the spare capacity
+        out.reserve(self.len());
+        out.extend(self.into_iter().map(f));
+    }
+
+    fn try_map_indexed<U, R: Try<Ok = U>, F: FnMut(usize, Self::T) -> R>(
+        self,
+        f: F,
+    ) -> Result<Vec<U>, R::Error> {
+        try_map_indexed(self, f)
+    }
+
+    fn try_filter_map<U, R: Try<Ok = Option<U>>, F: FnMut(Self::T) -> R>(
+        self,
+        f: F,
+    ) -> Result<Vec<U>, R::Error> {
+        if can_reuse() && Layout::new::<T>() == Layout::new::<U>() {
+            let iter = FilterMapIter {
+                read_len: 0,
+                write_len: 0,
+                data: Input::from(self),
+                drop: PhantomData,
+            };
+
+            iter.try_into_vec(f)
+        } else {
+            self.into_iter()
+                .map(f)
+                .map(R::into_result)
+                .filter_map(Result::transpose)
+                .collect()
+        }
+    }
+
+    fn map_while<U, F: FnMut(Self::T) -> Option<U>>(self, f: F) -> Vec<U> {
+        if can_reuse() && Layout::new::<T>() == Layout::new::<U>() {
+            let iter = FilterMapIter {
+                read_len: 0,
+                write_len: 0,
+                data: Input::from(self),
+                drop: PhantomData,
+            };
+
+            iter.map_while_into_vec(f)
+        } else {
+            self.into_iter().map_while(f).collect()
+        }
+    }
+
+    fn chunks_map<const N: usize, U, F: FnMut([Self::T; N]) -> U>(
+        mut self,
+        f: F,
+    ) -> (Vec<U>, Vec<T>) {
+        use core::mem::{align_of, size_of};
+
+        assert!(N != 0, "Tried to map chunks of size zero");
+
+        // split off the remainder first, so that the rest
+        // is a whole number of chunks
+        let remainder = self.split_off(self.len() - self.len() % N);
+
+        let iter = ChunksMapIter::<T, U, N> {
+            read_len: 0,
+            write_len: 0,
+            data: Input::from(self),
+            drop: PhantomData,
+        };
+
+        let vec = if can_reuse()
+            && align_of::<T>() == align_of::<U>()
+            && !is_zst::<T>()
+            && !is_zst::<U>()
+            && size_of::<U>() <= N * size_of::<T>()
+        {
+            iter.into_vec(f)
+        } else {
+            iter.collect(f)
+        };
+
+        (vec, remainder)
+    }
+
+    fn windows_map<U, F: FnMut(&[Self::T]) -> U>(self, window: usize, f: F) -> Vec<U> {
+        use core::mem::{align_of, size_of};
+
+        assert!(window != 0, "Tried to map windows of size zero");
+
+        if window > self.len() {
+            return Vec::new();
+        }
+
+        if can_reuse()
+            && align_of::<T>() == align_of::<U>()
+            && !is_zst::<T>()
+            && !is_zst::<U>()
+            && size_of::<U>() <= size_of::<T>()
+        {
+            let iter = WindowsMapIter {
+                read_len: 0,
+                write_len: 0,
+                data: Input::from(self),
+                drop: PhantomData,
+            };
+
+            iter.into_vec(window, f)
+        } else {
+            self.windows(window).map(f).collect()
+        }
+    }
+
+    fn into_map_iter<U, F: FnMut(Self::T) -> U>(self, f: F) -> MapIntoIter<T, U, F> {
+        MapIntoIter {
+            read_len: 0,
+            end: self.len(),
+            data: Input::from(self),
+            func: f,
+            drop: PhantomData,
+        }
+    }
+
+    fn flat_map<U, I: IntoIterator<Item = U>, F: FnMut(Self::T) -> I>(self, f: F) -> Vec<U> {
+        if can_reuse() && Layout::new::<T>() == Layout::new::<U>() {
+            let iter = FlatMapIter {
+                read_len: 0,
+                write_len: 0,
+                gap: 0,
+                data: Input::from(self),
+                drop: PhantomData,
+            };
+
+            iter.into_vec(f)
+        } else {
+            self.into_iter().flat_map(f).collect()
+        }
+    }
+
+    fn map_expanding<U, F: FnMut(Self::T, &mut Vec<U>)>(self, mut f: F) -> Vec<U> {
+        if can_reuse() && Layout::new::<T>() == Layout::new::<U>() {
+            let iter = FlatMapIter {
+                read_len: 0,
+                write_len: 0,
+                gap: 0,
+                data: Input::from(self),
+                drop: PhantomData,
+            };
+
+            iter.into_vec_expanding(f)
+        } else {
+            let mut vec = Vec::with_capacity(self.len());
+            let mut pending = Vec::new();
+
+            for value in self {
+                f(value, &mut pending);
+                vec.append(&mut pending);
+            }
+
+            vec
+        }
+    }
+
+    fn map_two_phase<U, C: FnMut(&Self::T) -> usize, F: FnMut(Self::T, &mut Vec<U>)>(
+        mut self,
+        mut count: C,
+        mut fill: F,
+    ) -> Vec<U> {
+        // `lead` is how far the output gets ahead of the input that was read,
+        // so the output never catches up to the unread input if there are
+        // at least `lead` free slots in front of it
+        let mut total = 0;
+        let mut lead = 0;
+
+        for (read_len, value) in self.iter().enumerate() {
+            total += count(value);
+            lead = lead.max(total.saturating_sub(read_len + 1));
+        }
+
+        let vec = if can_reuse() && Layout::new::<T>() == Layout::new::<U>() {
+            self.reserve_exact(lead);
+
+            let mut data = Input::from(self);
+            data.move_to_end();
+
+            // if the counts are right, then this never spills
+            let iter = FlatMapIter {
+                read_len: 0,
+                write_len: 0,
+                gap: data.cap - data.len,
+                data,
+                drop: PhantomData,
+            };
+
+            iter.into_vec_expanding(fill)
+        } else {
+            let mut vec = Vec::with_capacity(total);
+            let mut pending = Vec::new();
+
+            for value in self {
+                fill(value, &mut pending);
+                vec.append(&mut pending);
+            }
+
+            vec
+        };
+
+        debug_assert_eq!(
+            vec.len(),
+            total,
+            "The counted length didn't match the number of values that were filled"
+        );
+
+        vec
+    }
+
+    fn try_zip_with<U, V, R: Try<Ok = V>, F: FnMut(Self::T, U) -> R>(
+        mut self,
+        mut other: Vec<U>,
+        mut f: F,
+    ) -> Result<Vec<V>, R::Error> {
+        use core::mem::size_of;
+
+        // try_zip_with! { self, other => |x, y| { f(x, y) } }
+
+        // there are no allocations to reuse, and no elements to zip
+        if self.capacity() == 0 && other.capacity() == 0 {
+            return Ok(Vec::new());
+        }
+
+        // the capacities are compared in bytes, because the
+        // inputs may be reused for a `V` with a different size
+        let len = self.len().min(other.len());
+        let mut picked = pick_reuse_buffer(&[
+            (
+                fits::<T, V>(self.len(), self.capacity()),
+                self.capacity() * size_of::<T>(),
+            ),
+            (
+                fits::<U, V>(other.len(), other.capacity()),
+                other.capacity() * size_of::<U>(),
+            ),
+        ]);
+
+        if picked.is_none() {
+            // neither allocation can hold the output, so grow the larger one that
+            // has the same alignment as `V`, `realloc` can often do this in place
+            picked = pick_reuse_buffer(&[
+                (can_grow::<T, V>(), self.capacity() * size_of::<T>()),
+                (can_grow::<U, V>(), other.capacity() * size_of::<U>()),
+            ]);
+
+            match picked {
+                Some(0) => grow_for::<T, V>(&mut self),
+                Some(_) => grow_for::<U, V>(&mut other),
+                None => (),
+            }
+        }
+
+        match picked {
+            Some(0) => ZipWithIter {
+                init_len: len,
+                min_len: len,
+                drop: PhantomData,
+
+                left: Input::for_output::<V>(self),
+                right: Input::from(other),
+            }
+            .try_into_vec(f),
+            Some(_) => ZipWithIter {
+                init_len: len,
+                min_len: len,
+                drop: PhantomData,
+
+                left: Input::for_output::<V>(other),
+                right: Input::from(self),
+            }
+            .try_into_vec(move |y, x| f(x, y)),
+            None => self
+                .into_iter()
+                .zip(other)
+                .map(move |(x, y)| f(x, y))
+                .map(R::into_result)
+                .collect(),
+        }
+    }
+
+    fn try_zip_eq_with<U, V, R: Try<Ok = V>, F: FnMut(Self::T, U) -> R>(
+        self,
+        other: Vec<U>,
+        f: F,
+    ) -> Result<Vec<V>, R::Error> {
+        assert!(
+            self.len() == other.len(),
+            "Tried to zip vectors of different lengths, left: {}, right: {}",
+            self.len(),
+            other.len()
+        );
+
+        self.try_zip_with(other, f)
+    }
+
+    #[cfg(feature = "std")]
+    fn zip_into_map_with<U, F: FnMut(U, U) -> U>(
+        self,
+        values: Vec<U>,
+        mut combine: F,
+    ) -> std::collections::HashMap<Self::T, U>
+    where
+        Self::T: Eq + core::hash::Hash,
+    {
+        let mut map = std::collections::HashMap::with_capacity(self.len().min(values.len()));
+
+        // the earlier value is taken out of the map, so that
+        // it's never left in the map if `combine` panics
+        for (key, value) in self.into_iter().zip(values) {
+            let value = match map.remove(&key) {
+                Some(earlier) => combine(earlier, value),
+                None => value,
+            };
+
+            map.insert(key, value);
+        }
+
+        map
+    }
+
+    fn zip_with_until<U, V, F: FnMut(Self::T, U) -> (V, bool)>(
+        self,
+        other: Vec<U>,
+        mut f: F,
+    ) -> Vec<V> {
+        let mut done = false;
+
+        // `map_while` reuses the allocation that it maps over, and drops the rest of it's
+        // input when it stops, while the other vector's `vec::IntoIter` drops the rest of it
+        match pick_reuse_buffer(&[
+            (Layout::new::<T>() == Layout::new::<V>(), self.capacity()),
+            (Layout::new::<U>() == Layout::new::<V>(), other.capacity()),
+        ]) {
+            Some(1) => {
+                let mut left = self.into_iter();
+
+                other.map_while(move |y| {
+                    if done {
+                        return None;
+                    }
+
+                    let (value, stop) = f(left.next()?, y);
+                    done = stop;
+                    Some(value)
+                })
+            }
+            _ => {
+                let mut right = other.into_iter();
+
+                self.map_while(move |x| {
+                    if done {
+                        return None;
+                    }
+
+                    let (value, stop) = f(x, right.next()?);
+                    done = stop;
+                    Some(value)
+                })
+            }
+        }
+    }
+
+    fn zip_with_remainder<U, V, F: FnMut(Self::T, U) -> V>(
+        mut self,
+        mut other: Vec<U>,
+        f: F,
+    ) -> (Vec<V>, Remainder<Self::T, U>) {
+        use core::cmp::Ordering;
+
+        // split off the tail first, so that the zipped part can
+        // still reuse the allocation of the input vectors
+        let remainder = match self.len().cmp(&other.len()) {
+            Ordering::Greater => Remainder::Left(self.split_off(other.len())),
+            Ordering::Less => Remainder::Right(other.split_off(self.len())),
+            Ordering::Equal => Remainder::Neither,
+        };
+
+        (self.zip_with(other, f), remainder)
+    }
+
+    fn zip_with_default<U, V, F: FnMut(Self::T, U) -> V>(
+        mut self,
+        mut other: Vec<U>,
+        f: F,
+    ) -> Vec<V>
+    where
+        Self::T: Default,
+        U: Default,
+    {
+        // pad the shorter vector, so that nothing gets dropped by the zip,
+        // this will grow the shorter vector if it doesn't have enough capacity
+        let len = self.len().max(other.len());
+        self.resize_with(len, Default::default);
+        other.resize_with(len, Default::default);
+
+        self.zip_with(other, f)
+    }
+
+    fn interleave(self, other: Vec<T>) -> Vec<T> {
+        let (len_a, len_b) = (self.len(), other.len());
+        let len = len_a + len_b;
+
+        if !can_reuse() {
+            let mut vec = Vec::with_capacity(len);
+            let mut b = other.into_iter();
+
+            for x in self {
+                vec.push(x);
+                vec.extend(b.next());
+            }
+
+            vec.extend(b);
+            return vec;
+        }
+
+        let reuse_self = self.capacity() >= other.capacity();
+        let (mut vec, mut other) = if reuse_self {
+            (self, other)
+        } else {
+            (other, self)
+        };
+
+        vec.reserve_exact(other.len());
+
+        // nothing in here can panic, so there is no need for a guard
+        unsafe {
+            let start = vec.as_mut_ptr();
+            let moved = start.add(len - vec.len());
+
+            // move the elements of `vec` to the end of the allocation, that way
+            // they are always read before the output overwrites them
+            core::ptr::copy(start, moved, vec.len());
+
+            // the elements of `other` will be moved into `vec`
+            other.set_len(0);
+
+            let (a, b) = if reuse_self {
+                (moved as *const T, other.as_ptr())
+            } else {
+                (other.as_ptr(), moved as *const T)
+            };
+
+            let min_len = len_a.min(len_b);
+
+            for i in 0..min_len {
+                let x = a.add(i).read();
+                let y = b.add(i).read();
+
+                start.add(2 * i).write(x);
+                start.add(2 * i + 1).write(y);
+            }
+
+            let rest = if len_a > len_b { a } else { b };
+            core::ptr::copy(rest.add(min_len), start.add(2 * min_len), len - 2 * min_len);
+
+            vec.set_len(len);
+        }
+
+        vec
+    }
+
+    fn try_zip3_with<B, C, V, R: Try<Ok = V>, F: FnMut(Self::T, B, C) -> R>(
+        self,
+        b: Vec<B>,
+        c: Vec<C>,
+        mut f: F,
+    ) -> Result<Vec<V>, R::Error> {
+        // try_zip_with! { (self, b, c) => |x, y, z| { f(x, y, z) } }
+
+        try_zip_with_impl((self, (b, (c,))), move |(x, (y, z))| f(x, y, z))
+    }
+
+    #[cfg(feature = "rayon")]
+    fn par_map<U, F>(self, f: F) -> Vec<U>
+    where
+        F: Fn(Self::T) -> U + Sync + Send,
+        U: Send,
+        Self::T: Send,
+    {
+        par::par_map(self, f)
+    }
+
+    #[cfg(feature = "rayon")]
+    fn par_map_in_pool<U, F>(self, pool: &rayon::ThreadPool, f: F) -> Vec<U>
+    where
+        F: Fn(Self::T) -> U + Sync + Send,
+        U: Send,
+        Self::T: Send,
+    {
+        // the chunks are sized with `rayon::current_num_threads`,
+        // which is the number of threads in `pool` inside of `install`
+        pool.install(move || par::par_map(self, f))
+    }
+
+    #[cfg(feature = "portable_simd")]
+    fn map_simd<U, const LANES: usize, F>(self, f: F) -> Vec<U>
+    where
+        F: FnMut(core::simd::Simd<Self::T, LANES>) -> core::simd::Simd<U, LANES>,
+        Self::T: core::simd::SimdElement + Default,
+        U: core::simd::SimdElement,
+    {
+        simd::map_simd(self, f)
+    }
+
+    #[cfg(feature = "rayon")]
+    fn par_zip_with<U, V, F>(self, other: Vec<U>, f: F) -> Vec<V>
+    where
+        F: Fn(Self::T, U) -> V + Sync + Send,
+        U: Send,
+        V: Send,
+        Self::T: Send,
+    {
+        par::par_zip_with(self, other, f)
+    }
+
+    #[cfg(feature = "bumpalo")]
+    fn map_in_bump<'b, U, F: FnMut(Self::T) -> U>(
+        self,
+        bump: &'b bumpalo::Bump,
+        f: F,
+    ) -> bumpalo::collections::Vec<'b, U> {
+        let mut out = bumpalo::collections::Vec::with_capacity_in(self.len(), bump);
+
+        // `vec::IntoIter` drops the remaining inputs if `f` panics, and
+        // `out` has enough capacity, so this won't allocate again
+        out.extend(self.into_iter().map(f));
+
+        out
+    }
+
+    fn drop_and_reuse<U>(mut self) -> Vec<U> {
+        use core::mem::{align_of, size_of};
+
+        self.clear();
+
+        // Note: the alignments must match exactly, for the same reason as in `try_map`
+        if align_of::<T>() != align_of::<U>() || size_of::<T>() == 0 || size_of::<U>() == 0 {
+            return Vec::new();
+        }
+
+        if !can_reuse() {
+            // allocate a new vector with the same capacity that reusing would give
+            return Vec::with_capacity(self.capacity() * size_of::<T>() / size_of::<U>());
+        }
+
+        let mut vec = ManuallyDrop::new(self);
+
+        // no more elements in the vector
+        unsafe { reuse_alloc(vec.as_mut_ptr(), 0, vec.capacity()) }
+    }
+
+    fn wrap<W: TransparentWrapper<Self::T>>(self) -> Vec<W> {
+        unsafe { crate::transparent::cast_vec(self) }
+    }
+
+    fn unwrap_newtype<I>(self) -> Vec<I>
+    where
+        Self::T: TransparentWrapper<I>,
+    {
+        unsafe { crate::transparent::cast_vec(self) }
+    }
+
+    fn clear_and_reuse<U>(mut self) -> Vec<U> {
+        self.clear();
+
+        if !can_reuse() || Layout::new::<T>() != Layout::new::<U>() || is_zst::<U>() {
+            return Vec::new();
+        }
+
+        let mut vec = ManuallyDrop::new(self);
+
+        // the layouts match, so the allocation can be used as is
+        unsafe { Vec::from_raw_parts(vec.as_mut_ptr() as *mut U, 0, vec.capacity()) }
+    }
+
+    fn map_rev<U, F: FnMut(Self::T) -> U>(self, f: F) -> Vec<U> {
+        // writing a smaller or larger `U` while walking backwards would write over
+        // inputs that haven't been read yet, so only matching layouts are reused
+        if can_reuse() && Layout::new::<T>() == Layout::new::<U>() && !is_zst::<U>() {
+            MapRevIter {
+                init_len: 0,
+                data: Input::from(self),
+                drop: PhantomData,
+            }
+            .into_vec(f)
+        } else {
+            let mut vec = self.into_iter().rev().map(f).collect::<Vec<_>>();
+            vec.reverse();
+            vec
+        }
+    }
+
+    fn map_into_slice<U, F: FnMut(Self::T) -> U>(self, out: &mut [U], mut f: F) {
+        assert!(
+            self.len() == out.len(),
+            "Tried to map into a slice of a different length, vec: {}, slice: {}",
+            self.len(),
+            out.len()
+        );
+
+        for (slot, x) in out.iter_mut().zip(self) {
+            *slot = f(x);
+        }
+    }
+
+    fn map_into_uninit<U, F: FnMut(Self::T) -> U>(
+        self,
+        out: &mut [MaybeUninit<U>],
+        mut f: F,
+    ) -> &mut [U] {
+        assert!(
+            self.len() <= out.len(),
+            "Tried to map into a slice that is too short, vec: {}, slice: {}",
+            self.len(),
+            out.len()
+        );
+
+        let mut writer = UninitWriter {
+            start: out.as_mut_ptr() as *mut U,
+            init_len: 0,
+        };
+
+        // if `f` panics, the rest of the input is dropped by `IntoIter`
+        for x in self {
+            unsafe {
+                writer.start.add(writer.init_len).write(f(x));
+            }
+
+            writer.init_len += 1;
+        }
+
+        let writer = ManuallyDrop::new(writer);
+
+        unsafe { core::slice::from_raw_parts_mut(writer.start, writer.init_len) }
+    }
+
+    fn consume_fold<B, F: FnMut(B, Self::T) -> B>(self, init: B, mut f: F) -> B {
+        use core::convert::Infallible;
+
+        let iter = ConsumeIter {
+            read_len: 0,
+            data: Input::from(self),
+        };
+
+        match iter.try_fold(init, move |acc, x| Ok::<_, Infallible>(f(acc, x))) {
+            Ok(x) => x,
+            Err(x) => match x {},
+        }
+    }
+
+    #[cfg(feature = "std")]
+    fn map_send<U, F: FnMut(Self::T) -> U>(
+        self,
+        tx: std::sync::mpsc::SyncSender<U>,
+        mut f: F,
+    ) -> Result<(), std::sync::mpsc::SendError<U>> {
+        let iter = ConsumeIter {
+            read_len: 0,
+            data: Input::from(self),
+        };
+
+        iter.try_fold((), move |(), x| tx.send(f(x)))
+    }
+
+    fn try_reduce<R: Try<Ok = Self::T>, F: FnMut(Self::T, Self::T) -> R>(
+        self,
+        f: F,
+    ) -> Result<Option<Self::T>, R::Error> {
+        let mut iter = ConsumeIter {
+            read_len: 0,
+            data: Input::from(self),
+        };
+
+        match iter.pop_front() {
+            Some(first) => iter.try_fold(first, f).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn map_chunks_exact<const N: usize, F: FnMut(&mut [Self::T])>(self, f: F) {
+        assert!(N != 0, "Tried to map chunks of size zero");
+
+        ConsumeIter {
+            read_len: 0,
+            data: Input::from(self),
+        }
+        .for_each_chunk(N, f)
+    }
+
+    fn map_in_place<F: FnMut(&mut Self::T)>(&mut self, f: F) {
+        self.iter_mut().for_each(f)
+    }
+
+    fn zip_with_mut<U, F: FnMut(&mut Self::T, U)>(&mut self, other: Vec<U>, mut f: F) {
+        self.truncate(other.len());
+
+        // `vec::IntoIter` drops the rest of `other` when it's done, or if `f` panics
+        for (x, y) in self.iter_mut().zip(other) {
+            f(x, y);
+        }
+    }
+
+    fn replace_map<U, F: FnMut(Self::T) -> U>(&mut self, f: F) -> ReplaceMap<'_, T, U> {
+        assert!(
+            Layout::new::<T>() == Layout::new::<U>(),
+            "Tried to replace_map into a type with a different layout"
+        );
+
+        // take the vector out first, so that if `f` panics `self` is left empty
+        let vec = core::mem::take(self).map(f);
+
+        ReplaceMap::new(self, vec)
+    }
+
+    fn take_map<U, F: FnMut(Self::T) -> U>(&mut self, f: F) -> Vec<U> {
+        core::mem::take(self).map(f)
+    }
+
+    fn with_scratch<U, R, F: FnOnce(&mut ScratchVec<'_, U>) -> R>(&mut self, f: F) -> R {
+        f(&mut ScratchVec::new(self))
+    }
+}
+
+/// Turn a vector of options into an option of a vector, will try and reuse the
+/// allocation in the same cases as [`try_map`](VecExt::try_map)
+///
+/// This is all or nothing, if any of the elements are `None`, then all of the
+/// other values are dropped, and `None` is returned. The allocation is reused
+/// if `Option<T>` has the same layout as `T` (i.e. if `T` has a niche, like
+/// references or `Box<_>`), or if they have the same alignment.
+/// Otherwise, a new vector is allocated
+pub fn transpose_options<T>(vec: Vec<Option<T>>) -> Option<Vec<T>> {
+    vec.try_map(core::convert::identity).ok()
+}
+
+/// Turn a vector of results into a result of a vector, will try and reuse the
+/// allocation in the same cases as [`try_map`](VecExt::try_map)
+///
+/// On the first error, all of the other values are dropped, and the error is returned.
+/// The allocation is reused if `Result<T, E>` has the same layout as `T`, or if they
+/// have the same alignment. Otherwise, a new vector is allocated
+pub fn transpose_results<T, E>(vec: Vec<Result<T, E>>) -> Result<Vec<T>, E> {
+    vec.try_map(core::convert::identity)
+}
+
+/// Drop all of the `None`s in a vector of options, and keep the values in the `Some`s,
+/// will try and reuse the allocation in the same cases as [`filter_map`](VecExt::filter_map)
+///
+/// The values are moved down behind the unread options as they are read, so the
+/// allocation is reused if `Option<T>` has the same layout as `T` (i.e. if `T` has
+/// a niche, like references or `NonZeroU32`). Otherwise, a new vector is allocated
+pub fn flatten_options<T>(vec: Vec<Option<T>>) -> Vec<T> {
+    vec.filter_map(core::convert::identity)
+}
+
+/// Does the work of `try_map_alloc`, `map_with_capacity`, `map_reporting`, and `map_pooled`
+///
+/// Maps `vec` over it's own allocation if `MapIter` can reuse it, and passes the
+/// output to `reused`. Otherwise `vec` and `f` are given to `fallback`, which
+/// also handles vectors without an allocation
+fn map_or_else<T, U, F: FnMut(T) -> U, R>(
+    vec: Vec<T>,
+    mut f: F,
+    reused: impl FnOnce(Vec<U>) -> R,
+    fallback: impl FnOnce(Vec<T>, F) -> R,
+) -> R {
+    use core::convert::Infallible;
+
+    // there is no allocation to reuse
+    if vec.capacity() == 0 {
+        return fallback(vec, f);
+    }
+
+    match MapIter::new(vec) {
+        Ok(iter) => match iter.try_into_vec(|_, x| Ok::<_, Infallible>(f(x))) {
+            Ok(x) => reused(x),
+            Err(x) => match x {},
+        },
+        Err(vec) => fallback(vec, f),
+    }
+}
+
+/// Does the work of `try_map` and `try_map_indexed`
+fn try_map_indexed<T, U, R: Try<Ok = U>, F: FnMut(usize, T) -> R>(
+    vec: Vec<T>,
+    mut f: F,
+) -> Result<Vec<U>, R::Error> {
+    // there is no allocation to reuse, and no elements to map
+    if vec.capacity() == 0 {
+        return Ok(Vec::new());
+    }
+
+    match MapIter::new(vec) {
+        Ok(iter) => iter.try_into_vec(f),
+        Err(vec) => vec
+            .into_iter()
+            .enumerate()
+            .map(move |(i, x)| f(i, x))
+            .map(R::into_result)
+            .collect(),
+    }
+}
+
+/// Does the work of `try_map_resumable`
+#[allow(clippy::type_complexity)]
+fn try_map_resumable<T, U, R: Try<Ok = U>, F: FnMut(T) -> R>(
+    vec: Vec<T>,
+    mut f: F,
+) -> Result<Vec<U>, (Vec<U>, R::Error, Vec<T>)> {
+    match MapIter::new(vec) {
+        Ok(iter) => iter.try_into_vec_resumable(f),
+        Err(vec) => {
+            let mut out = Vec::with_capacity(vec.len());
+            let mut iter = vec.into_iter();
+
+            for x in iter.by_ref() {
+                match f(x).into_result() {
+                    Ok(value) => out.push(value),
+                    Err(err) => return Err((out, err, iter.collect())),
+                }
+            }
+
+            Ok(out)
+        }
+    }
+}
+
+/// Checks if allocations may be reused at all, this is turned off by the
+/// `disable-reuse` feature, so that every method takes the iterator path
+pub(crate) fn can_reuse() -> bool {
+    !cfg!(feature = "disable-reuse")
+}
+
+/// Checks if `T` is zero-sized, vectors of zero-sized types never allocate
+fn is_zst<T>() -> bool {
+    core::mem::size_of::<T>() == 0
+}
+
+/// Picks which input's allocation should be reused for the output
+///
+/// Each input is described by whether it's layout is compatible with the output,
+/// and it's capacity. This picks the compatible input with the largest capacity,
+/// so that the output is less likely to reallocate later. On ties, the earlier input
+/// is picked. Returns `None` if none of the inputs are compatible
+fn pick_reuse_buffer(inputs: &[(bool, usize)]) -> Option<usize> {
+    if !can_reuse() {
+        return None;
+    }
+
+    let mut picked: Option<(usize, usize)> = None;
+
+    for (i, &(compatible, cap)) in inputs.iter().enumerate() {
+        let is_larger = match picked {
+            Some((_, picked_cap)) => cap > picked_cap,
+            None => true,
+        };
+
+        if compatible && is_larger {
+            picked = Some((i, cap));
+        }
+    }
+
+    picked.map(|(i, _)| i)
+}
+
+/// Checks if an allocation of `T`s can be reused for a smaller `U`
+///
+/// This requires that the alignments match exactly, so that the allocation
+/// can be deallocated as a `Vec<U>`
+fn fits_smaller<T, U>() -> bool {
+    use core::mem::{align_of, size_of};
+
+    align_of::<T>() == align_of::<U>()
+        && size_of::<U>() != 0
+        && size_of::<U>() < size_of::<T>()
+}
+
+/// Checks if an allocation of `cap` `T`s, with `len` initialized elements
+/// can be reused for a larger `U`
+///
+/// This requires that the alignments match exactly, so that the allocation
+/// can be deallocated as a `Vec<U>`
+fn fits_larger<T, U>(len: usize, cap: usize) -> bool {
+    use core::mem::{align_of, size_of};
+
+    align_of::<T>() == align_of::<U>()
+        && size_of::<T>() != 0
+        && size_of::<U>() > size_of::<T>()
+        && len <= cap * size_of::<T>() / size_of::<U>()
+}
+
+/// Checks if an allocation of `T`s can be grown with `realloc` to hold a larger `U`
+///
+/// This requires that the alignments match exactly, so that the allocation
+/// can be deallocated as a `Vec<U>`
+fn can_grow<T, U>() -> bool {
+    use core::mem::{align_of, size_of};
+
+    align_of::<T>() == align_of::<U>() && size_of::<T>() != 0 && size_of::<U>() > size_of::<T>()
+}
+
+/// Grows the allocation of `vec` so that it has enough spare capacity
+/// to hold all of it's elements as `U`s, i.e. so that it `fits_larger`
+///
+/// This must only be called if `can_grow::<T, U>()`
+fn grow_for<T, U>(vec: &mut Vec<T>) {
+    use core::mem::size_of;
+
+    debug_assert!(can_grow::<T, U>());
+
+    let bytes = vec
+        .len()
+        .checked_mul(size_of::<U>())
+        .expect("capacity overflow");
+    let cap = bytes.div_ceil(size_of::<T>());
+
+    vec.reserve_exact(cap - vec.len());
+
+    debug_assert!(fits_larger::<T, U>(vec.len(), vec.capacity()));
+}
+
+/// Checks if an allocation of `cap` `T`s, with `len` initialized elements
+/// can be reused for `U`s, in any of the cases that `MapIter` handles
+fn fits<T, U>(len: usize, cap: usize) -> bool {
+    (Layout::new::<T>() == Layout::new::<U>() && !is_zst::<U>())
+        || fits_smaller::<T, U>()
+        || fits_larger::<T, U>(len, cap)
+}
+
+/// Turn an allocation that was made for `cap` `T`s into a `Vec<U>`,
+/// where the first `len` `U`s are initialized
+///
+/// If the size in bytes of the allocation is not a multiple of the size of `U`,
+/// the allocation is shrunk so that it can be deallocated as a `Vec<U>`
+///
+/// # Safety
+///
+/// * `ptr` and `cap` must come from a `Vec<T>`
+/// * `align_of::<T>() == align_of::<U>()`, and `U` is not zero-sized
+/// * the first `len` `U`s in the allocation must be initialized
+/// * `len * size_of::<U>() <= cap * size_of::<T>()`
+unsafe fn reuse_alloc<T, U>(ptr: *mut T, len: usize, cap: usize) -> Vec<U> {
+    use core::mem::size_of;
+
+    reuse_alloc_with_capacity(ptr, len, cap, cap * size_of::<T>() / size_of::<U>())
+}
+
+/// Turn an allocation that was made for `cap` `T`s into a `Vec<U>` with a capacity
+/// of `new_cap`, where the first `len` `U`s are initialized
+///
+/// If the size in bytes of the allocation doesn't match `new_cap` `U`s, then
+/// it's resized with a single `realloc`
+///
+/// # Safety
+///
+/// The same as [`reuse_alloc`], and `len <= new_cap`, and
+/// `new_cap * size_of::<U>() <= cap * size_of::<T>()`
+unsafe fn reuse_alloc_with_capacity<T, U>(
+    ptr: *mut T,
+    len: usize,
+    cap: usize,
+    new_cap: usize,
+) -> Vec<U> {
+    use core::mem::size_of;
+
+    let bytes = cap * size_of::<T>();
+    let new_bytes = new_cap * size_of::<U>();
+
+    if bytes == new_bytes {
+        return Vec::from_raw_parts(ptr as *mut U, len, new_cap);
+    }
+
+    let layout = Layout::array::<T>(cap).unwrap();
+
+    if new_cap == 0 {
+        alloc::alloc::dealloc(ptr as *mut u8, layout);
+        return Vec::new();
+    }
+
+    let new_ptr = alloc::alloc::realloc(ptr as *mut u8, layout, new_bytes);
+
+    if new_ptr.is_null() {
+        alloc::alloc::handle_alloc_error(Layout::from_size_align_unchecked(
+            new_bytes,
+            layout.align(),
+        ))
+    }
+
+    Vec::from_raw_parts(new_ptr as *mut U, len, new_cap)
+}
+
+struct MapIter<T, U> {
+    init_len: usize,
+
+    data: Input<T>,
+
+    // for drop check
+    drop: PhantomData<U>,
+}
+
+impl<T, U> MapIter<T, U> {
+    // Sets up a `MapIter` if the allocation of `vec` can be reused for `U`s,
+    // otherwise gives back `vec`
+    fn new(vec: Vec<T>) -> Result<Self, Vec<T>> {
+        // Note: the alignments must match exactly, not just be compatible,
+        // because the allocator must be given the same layout when the
+        // `Vec<U>` deallocates as the one used to allocate the `Vec<T>`
+        //
+        // zero-sized types don't have an allocation to reuse, so they take the
+        // iterator path, which never touches the dangling pointer
+        if !can_reuse() {
+            Err(vec)
+        } else if Layout::new::<T>() == Layout::new::<U>() && !is_zst::<U>() {
+            Ok(MapIter {
+                init_len: 0,
+                data: Input::from(vec),
+                drop: PhantomData,
+            })
+        } else if fits_smaller::<T, U>() {
+            // `U` is smaller than `T`, so the output is written behind the
+            // input, and will never overwrite any values that haven't been read yet
+            Ok(MapIter {
+                init_len: 0,
+                data: Input::from(vec),
+                drop: PhantomData,
+            })
+        } else if fits_larger::<T, U>(vec.len(), vec.capacity()) {
+            Ok(MapIter {
+                init_len: 0,
+                data: Input::for_output::<U>(vec),
+                drop: PhantomData,
+            })
+        } else {
+            Err(vec)
+        }
+    }
+
+    fn try_into_vec<R: Try<Ok = U>, F: FnMut(usize, T) -> R>(
+        mut self,
+        f: F,
+    ) -> Result<Vec<U>, R::Error> {
+        r#try!(self.try_fill(f));
+
+        let vec = ManuallyDrop::new(self);
+
+        // we don't want to free the memory
+        // which is what dropping this `MapIter` will do
+        unsafe {
+            if Layout::new::<T>() == Layout::new::<U>() {
+                Ok(Vec::from_raw_parts(
+                    vec.data.start as *mut U,
+                    vec.data.len,
+                    vec.data.cap,
+                ))
+            } else {
+                Ok(reuse_alloc(vec.data.start, vec.data.len, vec.data.cap))
+            }
+        }
+    }
+
+    // The same as `try_into_vec`, but the allocation is shrunk straight
+    // to the length of the output, instead of to a whole number of `U`s
+    fn try_into_vec_shrunk<R: Try<Ok = U>, F: FnMut(usize, T) -> R>(
+        mut self,
+        f: F,
+    ) -> Result<Vec<U>, R::Error> {
+        r#try!(self.try_fill(f));
+
+        let vec = ManuallyDrop::new(self);
+
+        unsafe {
+            Ok(reuse_alloc_with_capacity(
+                vec.data.start,
+                vec.data.len,
+                vec.data.cap,
+                vec.data.len,
+            ))
+        }
+    }
+
+    // Maps every value in the input, if `f` fails then the `MapIter` is left so that
+    // dropping it cleans up everything
+    fn try_fill<R: Try<Ok = U>, F: FnMut(usize, T) -> R>(
+        &mut self,
+        mut f: F,
+    ) -> Result<(), R::Error> {
+        if !core::mem::needs_drop::<T>() && !core::mem::needs_drop::<U>() {
+            // nothing needs to be dropped if `f` panics, so only the allocation
+            // has to be cleaned up, and there's no need to track how far we got.
+            // This keeps the loop free of stores to `self`, which helps LLVM vectorize it
+            for i in 0..self.data.len {
+                unsafe {
+                    let value = r#try!(f(i, self.data.ptr.add(i).read()));
+
+                    (self.data.start as *mut U).add(i).write(value);
+                }
+            }
+        } else {
+            // does a pointer walk, easy for LLVM to optimize
+            while self.init_len < self.data.len {
+                unsafe {
+                    let value = r#try!(f(self.init_len, self.data.ptr.read()));
+
+                    // if `U` is a different size than `T`, then this will not line up with `self.data.ptr`
+                    (self.data.start as *mut U).add(self.init_len).write(value);
+
+                    self.data.ptr = self.data.ptr.add(1);
+                    self.init_len += 1;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn try_into_vec_resumable<R: Try<Ok = U>, F: FnMut(T) -> R>(
+        mut self,
+        mut f: F,
+    ) -> Result<Vec<U>, (Vec<U>, R::Error, Vec<T>)> {
+        // does a pointer walk, easy for LLVM to optimize
+        while self.init_len < self.data.len {
+            unsafe {
+                let value = match f(self.data.ptr.read()).into_result() {
+                    Ok(value) => value,
+                    Err(err) => {
+                        let (out, rest) = self.split_rest();
+                        return Err((out, err, rest));
+                    }
+                };
+
+                (self.data.start as *mut U).add(self.init_len).write(value);
+
+                self.data.ptr = self.data.ptr.add(1);
+                self.init_len += 1;
+            }
+        }
+
+        let vec = ManuallyDrop::new(self);
+
+        // we don't want to free the memory
+        // which is what dropping this `MapIter` will do
+        unsafe {
+            if Layout::new::<T>() == Layout::new::<U>() {
+                Ok(Vec::from_raw_parts(
+                    vec.data.start as *mut U,
+                    vec.data.len,
+                    vec.data.cap,
+                ))
+            } else {
+                Ok(reuse_alloc(vec.data.start, vec.data.len, vec.data.cap))
+            }
+        }
+    }
+
+    // Splits the buffer into the output that was already written, and the input
+    // that wasn't read yet, the value at `data.ptr` was just read from, so it is skipped
+    //
+    // The rest of the input is moved into a new allocation, because the
+    // output has already overwritten the start of the buffer
+    unsafe fn split_rest(self) -> (Vec<U>, Vec<T>) {
+        let rest_len = self.data.len - self.init_len - 1;
+
+        // allocate before taking ownership of the buffer, so that `self`
+        // still cleans everything up if this fails
+        let mut rest = Vec::with_capacity(rest_len);
+
+        let this = ManuallyDrop::new(self);
+
+        core::ptr::copy_nonoverlapping(this.data.ptr.add(1), rest.as_mut_ptr(), rest_len);
+        rest.set_len(rest_len);
+
+        let out = if Layout::new::<T>() == Layout::new::<U>() {
+            Vec::from_raw_parts(this.data.start as *mut U, this.init_len, this.data.cap)
+        } else {
+            reuse_alloc(this.data.start, this.init_len, this.data.cap)
+        };
+
+        (out, rest)
+    }
+}
+
+impl<T, U> Drop for MapIter<T, U> {
+    fn drop(&mut self) {
+        // this is only dropped from inside the loop in `try_into_vec`,
+        // so there is always at least one element that was just read
+        //
+        // if neither `T` nor `U` need to be dropped, then `init_len` and `data.ptr`
+        // are not kept up to date, but then the slices below don't drop anything
+        debug_assert!(self.init_len < self.data.len);
+
+        unsafe {
+            // free the allocation, this happens last
+            defer! {
+                Vec::from_raw_parts(self.data.start, 0, self.data.cap);
+            }
+
+            // destroy the initialized output
+            defer! {
+                core::ptr::drop_in_place(core::ptr::slice_from_raw_parts_mut(
+                    self.data.start as *mut U,
+                    self.init_len,
+                ));
+            }
+
+            // offset by 1 because self.ptr is pointing to
+            // memory that was just read from, dropping that
+            // would lead to a double free
+            core::ptr::drop_in_place(core::ptr::slice_from_raw_parts_mut(
+                self.data.ptr.add(1),
+                self.data.len - self.init_len - 1,
+            ));
+        }
+    }
+}
+
+struct FilterMapIter<T, U> {
+    // the number of elements that have been read from the input
+    read_len: usize,
+
+    // the number of elements that have been written to the output,
+    // this will always be less than or equal to `read_len`
+    write_len: usize,
+
+    data: Input<T>,
+
+    // for drop check
+    drop: PhantomData<U>,
+}
+
+impl<T, U> FilterMapIter<T, U> {
+    fn try_into_vec<R: Try<Ok = Option<U>>, F: FnMut(T) -> R>(
+        mut self,
+        mut f: F,
+    ) -> Result<Vec<U>, R::Error> {
+        debug_assert_eq!(Layout::new::<T>(), Layout::new::<U>());
+
+        // does a pointer walk, the write cursor lags behind the read cursor
+        // whenever a value is filtered out
+        while self.read_len < self.data.len {
+            unsafe {
+                let value = r#try!(f(self.data.ptr.read()));
+
+                self.data.ptr = self.data.ptr.add(1);
+                self.read_len += 1;
+
+                if let Some(value) = value {
+                    (self.data.start as *mut U).add(self.write_len).write(value);
+                    self.write_len += 1;
+                }
+            }
+        }
+
+        let vec = ManuallyDrop::new(self);
+
+        // we don't want to free the memory
+        // which is what dropping this `FilterMapIter` will do
+        unsafe {
+            Ok(Vec::from_raw_parts(
+                vec.data.start as *mut U,
+                vec.write_len,
+                vec.data.cap,
+            ))
+        }
+    }
+}
+
+impl<T, U> FilterMapIter<T, U> {
+    fn map_while_into_vec<F: FnMut(T) -> Option<U>>(mut self, mut f: F) -> Vec<U> {
+        debug_assert_eq!(Layout::new::<T>(), Layout::new::<U>());
+
+        // does a pointer walk, the write cursor only lags behind the read cursor
+        // after the first `None`, which is where this stops
+        while self.read_len < self.data.len {
+            unsafe {
+                let value = f(self.data.ptr.read());
+
+                self.data.ptr = self.data.ptr.add(1);
+                self.read_len += 1;
+
+                let value = match value {
+                    Some(value) => value,
+                    None => break,
+                };
+
+                (self.data.start as *mut U).add(self.write_len).write(value);
+                self.write_len += 1;
+            }
+        }
+
+        let vec = ManuallyDrop::new(self);
+
+        unsafe {
+            // the value that returned `None` was already consumed by `f`,
+            // so only the unread input is left
+            let rest =
+                core::ptr::slice_from_raw_parts_mut(vec.data.ptr, vec.data.len - vec.read_len);
+
+            // create the vector now, so that if we panic in drop, we don't leak it
+            let out = Vec::from_raw_parts(vec.data.start as *mut U, vec.write_len, vec.data.cap);
+
+            core::ptr::drop_in_place(rest);
+
+            out
+        }
+    }
+}
+
+impl<T, U> Drop for FilterMapIter<T, U> {
+    fn drop(&mut self) {
+        // this is only dropped from inside the loop in `try_into_vec`,
+        // so there is always at least one element that was just read
+        debug_assert!(self.read_len < self.data.len);
+
+        unsafe {
+            // free the allocation, this happens last
+            defer! {
+                Vec::from_raw_parts(self.data.start, 0, self.data.cap);
+            }
+
+            // destroy the initialized output
+            defer! {
+                core::ptr::drop_in_place(core::ptr::slice_from_raw_parts_mut(
+                    self.data.start as *mut U,
+                    self.write_len,
+                ));
+            }
+
+            // offset by 1 because self.ptr is pointing to
+            // memory that was just read from, dropping that
+            // would lead to a double free
+            core::ptr::drop_in_place(core::ptr::slice_from_raw_parts_mut(
+                self.data.ptr.add(1),
+                self.data.len - self.read_len - 1,
+            ));
+        }
+    }
+}
+
+struct ChunksMapIter<T, U, const N: usize> {
+    // the number of chunks that have been read from the input,
+    // `data.ptr` always points to the first unread chunk
+    read_len: usize,
+
+    // the number of elements that have been written to the input allocation,
+    // this will always be less than or equal to `read_len`
+    write_len: usize,
+
+    data: Input<T>,
+
+    // for drop check
+    drop: PhantomData<U>,
+}
+
+impl<T, U, const N: usize> ChunksMapIter<T, U, N> {
+    // reads the next chunk, this must only be called if there are chunks left
+    unsafe fn next_chunk(&mut self) -> [T; N] {
+        let chunk = (self.data.ptr as *const [T; N]).read();
+
+        self.data.ptr = self.data.ptr.add(N);
+        self.read_len += 1;
+
+        chunk
+    }
+
+    // writes the output over the input allocation
+    fn into_vec<F: FnMut([T; N]) -> U>(mut self, mut f: F) -> Vec<U> {
+        let chunk_count = self.data.len / N;
+
+        // does a pointer walk, the write cursor lags behind the read cursor,
+        // and never overwrites a value that hasn't been read yet
+        while self.read_len < chunk_count {
+            unsafe {
+                let value = f(self.next_chunk());
+
+                (self.data.start as *mut U).add(self.write_len).write(value);
+                self.write_len += 1;
+            }
+        }
+
+        let vec = ManuallyDrop::new(self);
+
+        // we don't want to free the memory
+        // which is what dropping this `ChunksMapIter` will do
+        unsafe {
+            if Layout::new::<T>() == Layout::new::<U>() {
+                Vec::from_raw_parts(vec.data.start as *mut U, vec.write_len, vec.data.cap)
+            } else {
+                reuse_alloc(vec.data.start, vec.write_len, vec.data.cap)
+            }
+        }
+    }
+
+    // collects the output into a new allocation
+    fn collect<F: FnMut([T; N]) -> U>(mut self, mut f: F) -> Vec<U> {
+        let chunk_count = self.data.len / N;
+        let mut vec = Vec::with_capacity(chunk_count);
+
+        while self.read_len < chunk_count {
+            unsafe {
+                vec.push(f(self.next_chunk()));
+            }
+        }
+
+        vec
+    }
+}
+
+impl<T, U, const N: usize> Drop for ChunksMapIter<T, U, N> {
+    fn drop(&mut self) {
+        unsafe {
+            // free the allocation, this happens last
+            defer! {
+                Vec::from_raw_parts(self.data.start, 0, self.data.cap);
+            }
+
+            // destroy the initialized output, `collect` never writes into the input
+            // allocation, which may not be aligned for `U`, so there is nothing to drop
+            defer! {
+                if self.write_len != 0 {
+                    core::ptr::drop_in_place(core::ptr::slice_from_raw_parts_mut(
+                        self.data.start as *mut U,
+                        self.write_len,
+                    ));
+                }
+            }
+
+            // the chunks that were read were moved out,
+            // so only the unread input is left
+            core::ptr::drop_in_place(core::ptr::slice_from_raw_parts_mut(
+                self.data.ptr,
+                self.data.len - self.read_len * N,
+            ));
+        }
+    }
+}
+
+struct WindowsMapIter<T, U> {
+    // the number of elements that have been dropped from the front of the input,
+    // the rest of the input starts at `data.start.add(read_len)`
+    read_len: usize,
+
+    // the number of elements that have been written to the output,
+    // this will always be less than or equal to `read_len`
+    write_len: usize,
+
+    data: Input<T>,
+
+    // for drop check
+    drop: PhantomData<U>,
+}
+
+impl<T, U> WindowsMapIter<T, U> {
+    fn into_vec<F: FnMut(&[T]) -> U>(mut self, window: usize, mut f: F) -> Vec<U> {
+        debug_assert!(window != 0 && window <= self.data.len);
+
+        let out_len = self.data.len - window + 1;
+
+        // does a pointer walk, because `U` is not larger than `T`, the output
+        // only ever overlaps the elements that were already dropped
+        while self.write_len < out_len {
+            unsafe {
+                let start = self.data.start.add(self.read_len);
+                let value = f(core::slice::from_raw_parts(start, window));
+
+                // the first element of this window is not in any of the later windows
+                self.read_len += 1;
+                start.drop_in_place();
+
+                (self.data.start as *mut U).add(self.write_len).write(value);
+                self.write_len += 1;
+            }
+        }
+
+        let vec = ManuallyDrop::new(self);
+
+        unsafe {
+            let rest = core::ptr::slice_from_raw_parts_mut(
+                vec.data.start.add(vec.read_len),
+                vec.data.len - vec.read_len,
+            );
+
+            // create the vector now, so that if we panic in drop, we don't leak it
+            let out = if Layout::new::<T>() == Layout::new::<U>() {
+                Vec::from_raw_parts(vec.data.start as *mut U, vec.write_len, vec.data.cap)
+            } else {
+                reuse_alloc(vec.data.start, vec.write_len, vec.data.cap)
+            };
+
+            core::ptr::drop_in_place(rest);
+
+            out
+        }
+    }
+}
+
+impl<T, U> Drop for WindowsMapIter<T, U> {
+    fn drop(&mut self) {
+        unsafe {
+            // free the allocation, this happens last
+            defer! {
+                Vec::from_raw_parts(self.data.start, 0, self.data.cap);
+            }
+
+            // destroy the initialized output
+            defer! {
+                core::ptr::drop_in_place(core::ptr::slice_from_raw_parts_mut(
+                    self.data.start as *mut U,
+                    self.write_len,
+                ));
+            }
+
+            core::ptr::drop_in_place(core::ptr::slice_from_raw_parts_mut(
+                self.data.start.add(self.read_len),
+                self.data.len - self.read_len,
+            ));
+        }
+    }
+}
+
+/// A lazy iterator that maps the values of a `Vec<T>`, created by
+/// [`VecExt::into_map_iter`](trait.VecExt.html#method.into_map_iter)
+///
+/// [`into_vec`](MapIntoIter::into_vec) is the only way to reuse the input allocation,
+/// `collect` and the other iterator adapters always allocate a new vector
+pub struct MapIntoIter<T, U, F> {
+    // the unread input is always `read_len..end`, values are read from the front by
+    // `next` and from the back by `next_back`, which move these towards each other.
+    // Everything outside of that range was already moved out, so it isn't dropped
+    // again, and `read_len <= end <= data.len` always holds
+
+    // the number of elements that have been read from the front of the input,
+    // `data.ptr` always points to the first unread element
+    read_len: usize,
+
+    // the end of the unread input, the elements after this have
+    // been read from the back of the input
+    end: usize,
+
+    data: Input<T>,
+    func: F,
+
+    // for drop check
+    drop: PhantomData<U>,
+}
+
+impl<T, U, F: FnMut(T) -> U> MapIntoIter<T, U, F> {
+    /// Map the rest of the values into a vector, this will try and reuse the allocation
+    /// if the allocation layouts of the two types match, i.e. if
+    /// `std::alloc::Layout::<T>::new() == std::alloc::Layout::<U>::new()`
+    ///
+    /// The values that were already yielded from the iterator will not be in the vector
+    pub fn into_vec(self) -> Vec<U> {
+        if can_reuse() && Layout::new::<T>() == Layout::new::<U>() && !is_zst::<U>() {
+            let this = ManuallyDrop::new(self);
+
+            // move everything out of `this`, so that the
+            // `FilterMapIter` takes over cleaning up
+            let (mut data, mut f) =
+                unsafe { (core::ptr::read(&this.data), core::ptr::read(&this.func)) };
+
+            // the values after `end` were already read from the back,
+            // so the `FilterMapIter` should stop before them
+            data.len = this.end;
+
+            let iter = FilterMapIter {
+                read_len: this.read_len,
+                write_len: 0,
+                data,
+                drop: PhantomData,
+            };
+
+            match iter.try_into_vec(move |x| Ok::<_, core::convert::Infallible>(Some(f(x)))) {
+                Ok(x) => x,
+                Err(x) => match x {},
+            }
+        } else {
+            let mut vec = Vec::with_capacity(self.len());
+            vec.extend(self);
+            vec
+        }
+    }
+}
+
+impl<T, U, F: FnMut(T) -> U> Iterator for MapIntoIter<T, U, F> {
+    type Item = U;
+
+    fn next(&mut self) -> Option<U> {
+        if self.read_len < self.end {
+            unsafe {
+                let value = self.data.ptr.read();
+
+                self.data.ptr = self.data.ptr.add(1);
+                self.read_len += 1;
+
+                Some((self.func)(value))
+            }
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.end - self.read_len;
+        (len, Some(len))
+    }
+}
+
+impl<T, U, F: FnMut(T) -> U> DoubleEndedIterator for MapIntoIter<T, U, F> {
+    fn next_back(&mut self) -> Option<U> {
+        if self.read_len < self.end {
+            unsafe {
+                // move `end` first, so that the value isn't dropped again if `f` panics
+                self.end -= 1;
+
+                let value = self.data.start.add(self.end).read();
+
+                Some((self.func)(value))
+            }
+        } else {
+            None
+        }
+    }
+}
+
+impl<T, U, F: FnMut(T) -> U> ExactSizeIterator for MapIntoIter<T, U, F> {}
+
+impl<T, U, F> Drop for MapIntoIter<T, U, F> {
+    fn drop(&mut self) {
+        unsafe {
+            // free the allocation, this happens last
+            defer! {
+                Vec::from_raw_parts(self.data.start, 0, self.data.cap);
+            }
+
+            // the values that were already read from either end were
+            // moved out, so only the unread input is left
+            core::ptr::drop_in_place(core::ptr::slice_from_raw_parts_mut(
+                self.data.ptr,
+                self.end - self.read_len,
+            ));
+        }
+    }
+}
+
+// This owns the input vector while it is being consumed, so that the
+// rest of the input is dropped if the function consuming it panics
+struct ConsumeIter<T> {
+    // the number of elements that have been read from the input,
+    // the rest of the input starts at `data.start.add(read_len)`
+    read_len: usize,
+
+    data: Input<T>,
+}
+
+impl<T> ConsumeIter<T> {
+    fn pop_front(&mut self) -> Option<T> {
+        if self.read_len == self.data.len {
+            return None;
+        }
+
+        unsafe {
+            let value = self.data.start.add(self.read_len).read();
+
+            // the value was moved out, so it shouldn't be dropped again
+            self.read_len += 1;
+
+            Some(value)
+        }
+    }
+
+    fn try_fold<B, R: Try<Ok = B>, F: FnMut(B, T) -> R>(
+        mut self,
+        init: B,
+        mut f: F,
+    ) -> Result<B, R::Error> {
+        let mut acc = init;
+
+        while let Some(value) = self.pop_front() {
+            acc = r#try!(f(acc, value));
+        }
+
+        Ok(acc)
+    }
+
+    fn for_each_chunk<F: FnMut(&mut [T])>(mut self, chunk_len: usize, mut f: F) {
+        while self.read_len < self.data.len {
+            let len = chunk_len.min(self.data.len - self.read_len);
+
+            unsafe {
+                let chunk =
+                    core::ptr::slice_from_raw_parts_mut(self.data.start.add(self.read_len), len);
+
+                f(&mut *chunk);
+
+                // the chunk is dropped right away, so it shouldn't be dropped again
+                self.read_len += len;
+                core::ptr::drop_in_place(chunk);
+            }
+        }
+    }
+}
+
+impl<T> Drop for ConsumeIter<T> {
+    fn drop(&mut self) {
+        unsafe {
+            // free the allocation, this happens last
+            defer! {
+                Vec::from_raw_parts(self.data.start, 0, self.data.cap);
+            }
+
+            core::ptr::drop_in_place(core::ptr::slice_from_raw_parts_mut(
+                self.data.start.add(self.read_len),
+                self.data.len - self.read_len,
+            ));
+        }
+    }
+}
+
+// This is like `MapIter`, but it walks the input from back to front,
+// so the output is at the back of the buffer, and the input is at the front
+struct MapRevIter<T, U> {
+    // the number of outputs at the end of the buffer,
+    // the input is `data.start..data.start.add(data.len - init_len)`
+    init_len: usize,
+
+    data: Input<T>,
+
+    // for drop check
+    drop: PhantomData<U>,
+}
+
+impl<T, U> MapRevIter<T, U> {
+    fn into_vec<F: FnMut(T) -> U>(mut self, mut f: F) -> Vec<U> {
+        debug_assert_eq!(Layout::new::<T>(), Layout::new::<U>());
+
+        // does a pointer walk from the back, easy for LLVM to optimize
+        while self.init_len < self.data.len {
+            unsafe {
+                let ptr = self.data.start.add(self.data.len - self.init_len - 1);
+                let value = f(ptr.read());
+
+                (ptr as *mut U).write(value);
+                self.init_len += 1;
+            }
+        }
+
+        let vec = ManuallyDrop::new(self);
+
+        // we don't want to free the memory
+        // which is what dropping this `MapRevIter` will do
+        unsafe { Vec::from_raw_parts(vec.data.start as *mut U, vec.data.len, vec.data.cap) }
+    }
+}
+
+impl<T, U> Drop for MapRevIter<T, U> {
+    fn drop(&mut self) {
+        // the value at `data.len - init_len - 1` was just read from,
+        // so only the values before it still need to be dropped
+        debug_assert!(self.init_len < self.data.len);
+        let rest = self.data.len - self.init_len - 1;
+
+        unsafe {
+            // free the allocation, this happens last
+            defer! {
+                Vec::from_raw_parts(self.data.start, 0, self.data.cap);
+            }
+
+            // destroy the initialized output
+            defer! {
+                core::ptr::drop_in_place(core::ptr::slice_from_raw_parts_mut(
+                    self.data.start.add(rest + 1) as *mut U,
+                    self.init_len,
+                ));
+            }
+
+            core::ptr::drop_in_place(core::ptr::slice_from_raw_parts_mut(self.data.start, rest));
+        }
+    }
+}
+
+struct FlatMapIter<T, U> {
+    // the number of elements that have been read from the input,
+    // `data.ptr` always points to the first unread element
+    read_len: usize,
+
+    // the number of elements that have been written to the output,
+    // this will always be less than or equal to `read_len + gap`
+    write_len: usize,
+
+    // the number of free slots in front of the input before anything is read,
+    // this is zero unless the input was moved to the end of the allocation
+    gap: usize,
+
+    data: Input<T>,
+
+    // for drop check
+    drop: PhantomData<U>,
+}
+
+impl<T, U> FlatMapIter<T, U> {
+    fn into_vec<I: IntoIterator<Item = U>, F: FnMut(T) -> I>(mut self, mut f: F) -> Vec<U> {
+        debug_assert_eq!(Layout::new::<T>(), Layout::new::<U>());
+
+        // does a pointer walk, the write cursor stays behind the read cursor
+        // until the output catches up to the unread input
+        while self.read_len < self.data.len {
+            unsafe {
+                let value = self.data.ptr.read();
+
+                self.data.ptr = self.data.ptr.add(1);
+                self.read_len += 1;
+
+                let mut iter = f(value).into_iter();
+
+                while let Some(value) = iter.next() {
+                    if self.write_len == self.read_len + self.gap {
+                        let (mut vec, rest) = self.spill();
+
+                        vec.push(value);
+                        vec.extend(iter);
+                        vec.extend(rest.into_iter().flat_map(f));
+                        return vec;
+                    }
+
+                    (self.data.start as *mut U).add(self.write_len).write(value);
+                    self.write_len += 1;
+                }
+            }
+        }
+
+        self.finish()
+    }
+
+    fn into_vec_expanding<F: FnMut(T, &mut Vec<U>)>(mut self, mut f: F) -> Vec<U> {
+        debug_assert_eq!(Layout::new::<T>(), Layout::new::<U>());
+
+        // `f` pushes into this buffer instead of the output, so that it can't
+        // write over the unread input, the values are moved into the output after
+        let mut pending = Vec::new();
+
+        while self.read_len < self.data.len {
+            unsafe {
+                // the input is read before `f` is called, so the output
+                // may be written into it's slot
+                let value = self.data.ptr.read();
+
+                self.data.ptr = self.data.ptr.add(1);
+                self.read_len += 1;
+
+                f(value, &mut pending);
+
+                let mut iter = pending.drain(..);
+
+                while let Some(value) = iter.next() {
+                    if self.write_len == self.read_len + self.gap {
+                        let (mut vec, rest) = self.spill();
+
+                        vec.push(value);
+                        vec.extend(iter);
+
+                        for value in rest {
+                            f(value, &mut pending);
+                            vec.append(&mut pending);
+                        }
+
+                        return vec;
+                    }
+
+                    (self.data.start as *mut U).add(self.write_len).write(value);
+                    self.write_len += 1;
+                }
+            }
+        }
+
+        self.finish()
+    }
+
+    fn finish(self) -> Vec<U> {
+        let vec = ManuallyDrop::new(self);
+
+        // we don't want to free the memory
+        // which is what dropping this `FlatMapIter` will do
+        unsafe { Vec::from_raw_parts(vec.data.start as *mut U, vec.write_len, vec.data.cap) }
+    }
+
+    // The output has caught up to the unread input, so move the unread input
+    // out of the way, so that the output can continue as a normal growable `Vec`
+    unsafe fn spill(self) -> (Vec<U>, Vec<T>) {
+        let rest_len = self.data.len - self.read_len;
+
+        // allocate before taking ownership of the buffer, so that `self`
+        // still cleans everything up if this fails
+        let mut rest = Vec::<T>::with_capacity(rest_len);
+
+        let this = ManuallyDrop::new(self);
+
+        core::ptr::copy_nonoverlapping(this.data.ptr, rest.as_mut_ptr(), rest_len);
+        rest.set_len(rest_len);
+
+        let vec = Vec::from_raw_parts(this.data.start as *mut U, this.write_len, this.data.cap);
+
+        (vec, rest)
+    }
+}
+
+impl<T, U> Drop for FlatMapIter<T, U> {
+    fn drop(&mut self) {
+        unsafe {
+            // free the allocation, this happens last
+            defer! {
+                Vec::from_raw_parts(self.data.start, 0, self.data.cap);
+            }
+
+            // destroy the initialized output
+            defer! {
+                core::ptr::drop_in_place(core::ptr::slice_from_raw_parts_mut(
+                    self.data.start as *mut U,
+                    self.write_len,
+                ));
+            }
+
+            // the value that `f` was called on was already moved out,
+            // so only the unread input is left
+            core::ptr::drop_in_place(core::ptr::slice_from_raw_parts_mut(
+                self.data.ptr,
+                self.data.len - self.read_len,
+            ));
+        }
+    }
+}
+
+// Tracks the values written to uninitialized storage,
+// so that they can be dropped if the mapping function panics
+struct UninitWriter<U> {
+    start: *mut U,
+    init_len: usize,
+}
+
+impl<U> Drop for UninitWriter<U> {
+    fn drop(&mut self) {
+        unsafe {
+            core::ptr::drop_in_place(core::ptr::slice_from_raw_parts_mut(
+                self.start,
+                self.init_len,
+            ));
+        }
+    }
+}
+
+// The size of these structures don't matter since they are transient
+// So I didn't bother optimizing the size of them, and instead put all the
+// useful information I wanted, so that it could be initialized all at once
+struct ZipWithIter<T, U, V> {
+    // This left buffer is the one that will be reused
+    // to write the output into
+    left: Input<T>,
+
+    // We will only read from this buffer
+    //
+    // I considered using `std::vec::IntoIter`, but that lead to worse code
+    // because LLVM wasn't able to elide the bounds check on the iterator
+    right: Input<U>,
+
+    // the length of the output that has been written to
+    init_len: usize,
+    // the length of the vectors that must be traversed
+    min_len: usize,
+
+    // for drop check
+    drop: PhantomData<V>,
+}
+
+impl<T, U, V> ZipWithIter<T, U, V> {
+    fn try_into_vec<R: Try<Ok = V>, F: FnMut(T, U) -> R>(
+        mut self,
+        mut f: F,
+    ) -> Result<Vec<V>, R::Error> {
+        debug_assert!(fits::<T, V>(self.left.len, self.left.cap));
+
+        // this does a pointer walk and reads from left and right in lock-step
+        // then passes those values to the function to be processed
+        while let Some(min_len) = self.min_len.checked_sub(1) {
+            unsafe {
+                self.min_len = min_len;
+
+                // if `V` is a different size than `T`, then the output
+                // will not line up with `self.left.ptr`
+                let out = if Layout::new::<T>() == Layout::new::<V>() {
+                    self.left.ptr as *mut V
+                } else {
+                    (self.left.start as *mut V).add(self.init_len - min_len - 1)
+                };
+                let left = self.left.ptr;
+                let right = self.right.ptr;
+
+                self.left.ptr = self.left.ptr.add(1);
+                self.right.ptr = self.right.ptr.add(1);
+
+                let value = r#try!(f(left.read(), right.read()));
+
+                out.write(value);
+            }
+        }
+
+        // We don't want to drop `self` if dropping the excess elements panics
+        // as that could lead to double drops
+        let vec = ManuallyDrop::new(self);
+
+        unsafe {
+            // if we panic in drop, this drops the output and frees the
+            // allocation, so that we don't leak it
+            let output = crate::OnDrop(Some(|| {
+                defer! {
+                    Vec::from_raw_parts(vec.left.start, 0, vec.left.cap);
+                }
+
+                core::ptr::drop_in_place(core::ptr::slice_from_raw_parts_mut(
+                    vec.left.start as *mut V,
+                    vec.init_len,
+                ));
+            }));
+
+            {
+                // yay for defers running in reverse order and cleaning up the
+                // old vecs properly
+
+                // cleans up the right vec
+                defer! {
+                    Vec::from_raw_parts(vec.right.start, 0, vec.right.cap);
+                }
+
+                // drops the remaining elements of the right vec
+                defer! {
+                    core::ptr::drop_in_place(core::ptr::slice_from_raw_parts_mut(
+                        vec.right.ptr,
+                        vec.right.len - vec.init_len
+                    ));
+                }
+
+                // drop the remaining elements of the left vec
+                core::ptr::drop_in_place(core::ptr::slice_from_raw_parts_mut(
+                    vec.left.ptr,
+                    vec.left.len - vec.init_len,
+                ));
+            }
+
+            core::mem::forget(output);
+
+            // the allocation is only shrunk after the remaining elements of
+            // the left vec are dropped, because shrinking it may move them
+            if Layout::new::<T>() == Layout::new::<V>() {
+                Ok(Vec::from_raw_parts(
+                    vec.left.start as *mut V,
+                    vec.init_len,
+                    vec.left.cap,
+                ))
+            } else {
+                Ok(reuse_alloc(vec.left.start, vec.init_len, vec.left.cap))
+            }
+        }
+    }
+}
+
+impl<T, U, V> Drop for ZipWithIter<T, U, V> {
+    fn drop(&mut self) {
+        // this is only dropped from inside the loop in `try_into_vec`, after
+        // `min_len` was decremented, so at least one pair was just read
+        debug_assert!(self.min_len < self.init_len);
+
         unsafe {
+            // the number of pairs that were read, including the pair that was just read
             let len = self.init_len - self.min_len;
 
             // This will happen last
@@ -362,17 +3544,18 @@ impl<T, U, V> Drop for ZipWithIter<T, U, V> {
             //
             // They free the remaining parts of the two input vectors
             defer! {
-                std::ptr::drop_in_place(std::slice::from_raw_parts_mut(self.right.ptr, self.right.len - len));
+                core::ptr::drop_in_place(core::ptr::slice_from_raw_parts_mut(self.right.ptr, self.right.len - len));
             }
 
             defer! {
-                std::ptr::drop_in_place(std::slice::from_raw_parts_mut(self.left.ptr, self.left.len - len));
+                core::ptr::drop_in_place(core::ptr::slice_from_raw_parts_mut(self.left.ptr, self.left.len - len));
             }
 
-            // drop the output that we already calculated
-            std::ptr::drop_in_place(std::slice::from_raw_parts_mut(
+            // drop the output that we already calculated, this saturates so that
+            // if no pairs were read, it can't wrap around to a huge slice
+            core::ptr::drop_in_place(core::ptr::slice_from_raw_parts_mut(
                 self.left.start as *mut V,
-                len - 1,
+                len.saturating_sub(1),
             ));
         }
     }
@@ -0,0 +1,255 @@
+use alloc::alloc::{AllocError, Allocator};
+use alloc::vec::Vec;
+use core::alloc::Layout;
+use core::marker::PhantomData;
+use core::mem::ManuallyDrop;
+
+use super::{can_reuse, r#try, Try};
+
+/// Extension methods for `Vec<T, A>` with a custom allocator
+///
+/// These work like the methods on [`VecExt`](trait.VecExt.html), but the output
+/// is always allocated in the allocator of `self`, so a reused allocation is never
+/// freed with the wrong allocator. The allocation of `self` will be reused if
+/// `std::alloc::Layout::<T>::new() == std::alloc::Layout::<U>::new()`
+///
+/// This is only available with the `allocator_api` feature, which requires nightly
+pub trait VecInExt<A: Allocator>: Sized {
+    /// The type that the `Vec<T, A>` stores
+    type T;
+
+    /// Map a vector to another vector in the same allocator, will try and reuse the allocation
+    fn map_in<U, F: FnMut(Self::T) -> U>(self, mut f: F) -> Vec<U, A> {
+        use core::convert::Infallible;
+
+        match self.try_map_in(move |x| Ok::<_, Infallible>(f(x))) {
+            Ok(x) => x,
+            Err(x) => match x {},
+        }
+    }
+
+    /// Map a vector to another vector in the same allocator, will try and reuse the allocation
+    ///
+    /// The mapping function can be fallible, and on early return, it will drop all previous values,
+    /// and the rest of the input vector. The error will be returned as a `Result`
+    fn try_map_in<U, R: Try<Ok = U>, F: FnMut(Self::T) -> R>(
+        self,
+        f: F,
+    ) -> Result<Vec<U, A>, R::Error>;
+
+    /// Map a vector to another vector in the same allocator, will try and reuse the allocation
+    ///
+    /// If the allocation can't be reused, then the output is allocated with `try_reserve`
+    /// in the allocator of `self`, so if the allocator fails, the input vector is dropped and
+    /// `AllocError` is returned instead of aborting. If the allocation is reused, this can't fail
+    fn try_map_alloc_in<U, F: FnMut(Self::T) -> U>(self, f: F) -> Result<Vec<U, A>, AllocError>;
+
+    /// Zip a vector to another vector and combine them, the output is allocated
+    /// in the allocator of `self`, and the allocation of `self` will be reused if possible
+    fn zip_with_in<U, B: Allocator, V, F: FnMut(Self::T, U) -> V>(
+        self,
+        other: Vec<U, B>,
+        mut f: F,
+    ) -> Vec<V, A> {
+        use core::convert::Infallible;
+
+        match self.try_zip_with_in(other, move |x, y| Ok::<_, Infallible>(f(x, y))) {
+            Ok(x) => x,
+            Err(x) => match x {},
+        }
+    }
+
+    /// Zip a vector to another vector and combine them, the output is allocated
+    /// in the allocator of `self`, and the allocation of `self` will be reused if possible
+    ///
+    /// The mapping function can be fallible, and on early return, it will drop all previous values,
+    /// and the rest of the input vectors. The error will be returned as a `Result`
+    fn try_zip_with_in<U, B: Allocator, V, R: Try<Ok = V>, F: FnMut(Self::T, U) -> R>(
+        self,
+        other: Vec<U, B>,
+        f: F,
+    ) -> Result<Vec<V, A>, R::Error>;
+}
+
+impl<T, A: Allocator + Clone> VecInExt<A> for Vec<T, A> {
+    type T = T;
+
+    fn try_map_in<U, R: Try<Ok = U>, F: FnMut(Self::T) -> R>(
+        self,
+        mut f: F,
+    ) -> Result<Vec<U, A>, R::Error> {
+        if can_reuse() && Layout::new::<T>() == Layout::new::<U>() {
+            let len = self.len();
+
+            MapInIter::new(self).try_into_vec(len, f)
+        } else {
+            let mut vec = Vec::with_capacity_in(self.len(), self.allocator().clone());
+
+            for x in self {
+                vec.push(r#try!(f(x)));
+            }
+
+            Ok(vec)
+        }
+    }
+
+    fn try_map_alloc_in<U, F: FnMut(Self::T) -> U>(
+        self,
+        mut f: F,
+    ) -> Result<Vec<U, A>, AllocError> {
+        use core::convert::Infallible;
+
+        if can_reuse() && Layout::new::<T>() == Layout::new::<U>() {
+            let len = self.len();
+
+            match MapInIter::new(self).try_into_vec(len, move |x| Ok::<_, Infallible>(f(x))) {
+                Ok(x) => Ok(x),
+                Err(x) => match x {},
+            }
+        } else {
+            let mut vec = Vec::new_in(self.allocator().clone());
+            vec.try_reserve_exact(self.len()).map_err(|_| AllocError)?;
+
+            // `vec` already has enough capacity, so this won't allocate
+            vec.extend(self.into_iter().map(f));
+
+            Ok(vec)
+        }
+    }
+
+    fn try_zip_with_in<U, B: Allocator, V, R: Try<Ok = V>, F: FnMut(Self::T, U) -> R>(
+        self,
+        other: Vec<U, B>,
+        mut f: F,
+    ) -> Result<Vec<V, A>, R::Error> {
+        let len = self.len().min(other.len());
+        let mut other = other.into_iter();
+
+        if can_reuse() && Layout::new::<T>() == Layout::new::<V>() {
+            MapInIter::new(self).try_into_vec(len, move |x| match other.next() {
+                Some(y) => f(x, y),
+                None => unreachable!(),
+            })
+        } else {
+            let mut vec = Vec::with_capacity_in(len, self.allocator().clone());
+
+            for (x, y) in self.into_iter().zip(other) {
+                vec.push(r#try!(f(x, y)));
+            }
+
+            Ok(vec)
+        }
+    }
+}
+
+// This owns the input vector while it is being mapped, so that the
+// allocation is always freed by the allocator that created it
+struct MapInIter<T, U, A: Allocator> {
+    // the length of this vector is set to zero, the elements are tracked by
+    // `len` and `init_len` instead
+    vec: ManuallyDrop<Vec<T, A>>,
+    start: *mut T,
+    len: usize,
+    init_len: usize,
+
+    // for drop check
+    drop: PhantomData<U>,
+}
+
+impl<T, U, A: Allocator> MapInIter<T, U, A> {
+    fn new(vec: Vec<T, A>) -> Self {
+        let mut vec = ManuallyDrop::new(vec);
+        let len = vec.len();
+
+        unsafe {
+            vec.set_len(0);
+        }
+
+        Self {
+            start: vec.as_mut_ptr(),
+            vec,
+            len,
+            init_len: 0,
+            drop: PhantomData,
+        }
+    }
+
+    // maps the first `take` elements, and drops the rest
+    fn try_into_vec<R: Try<Ok = U>, F: FnMut(T) -> R>(
+        mut self,
+        take: usize,
+        mut f: F,
+    ) -> Result<Vec<U, A>, R::Error> {
+        debug_assert_eq!(Layout::new::<T>(), Layout::new::<U>());
+        debug_assert!(take <= self.len);
+
+        // does a pointer walk, easy for LLVM to optimize
+        while self.init_len < take {
+            unsafe {
+                let ptr = self.start.add(self.init_len);
+                let value = r#try!(f(ptr.read()));
+
+                (ptr as *mut U).write(value);
+                self.init_len += 1;
+            }
+        }
+
+        let mut this = ManuallyDrop::new(self);
+
+        unsafe {
+            // take the vector out of `this`, so that dropping
+            // it doesn't free the allocation
+            let vec = ManuallyDrop::take(&mut this.vec);
+            let cap = vec.capacity();
+            let alloc = core::ptr::read(vec.allocator());
+            core::mem::forget(vec);
+
+            let rest = core::ptr::slice_from_raw_parts_mut(this.start.add(take), this.len - take);
+
+            // create the vector now, so that if we panic in drop, we don't leak it
+            let out = Vec::from_raw_parts_in(this.start as *mut U, take, cap, alloc);
+
+            core::ptr::drop_in_place(rest);
+
+            Ok(out)
+        }
+    }
+}
+
+impl<T, U, A: Allocator> Drop for MapInIter<T, U, A> {
+    fn drop(&mut self) {
+        let &mut MapInIter {
+            ref mut vec,
+            start,
+            len,
+            init_len,
+            ..
+        } = self;
+
+        // this is only dropped from inside the loop in `try_into_vec`,
+        // so there is always at least one element that was just read
+        debug_assert!(init_len < len);
+
+        unsafe {
+            // free the allocation with it's own allocator, this happens last
+            defer! {
+                ManuallyDrop::drop(vec);
+            }
+
+            // destroy the initialized output
+            defer! {
+                core::ptr::drop_in_place(core::ptr::slice_from_raw_parts_mut(
+                    start as *mut U,
+                    init_len,
+                ));
+            }
+
+            // offset by 1 because the value at `init_len` was
+            // just read from, dropping that would lead to a double free
+            core::ptr::drop_in_place(core::ptr::slice_from_raw_parts_mut(
+                start.add(init_len + 1),
+                len - init_len - 1,
+            ));
+        }
+    }
+}
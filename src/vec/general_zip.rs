@@ -1,11 +1,13 @@
-use std::alloc::Layout;
+use alloc::vec::Vec;
+use core::alloc::Layout;
 
-use super::{r#try, Input, Output, Try};
+use super::{can_reuse, pick_reuse_buffer, r#try, Input, Output, Try};
 
 use seal::Seal;
 mod seal {
     use super::*;
 
+    #[allow(clippy::missing_safety_doc)]
     pub unsafe trait Seal {
         const LEN: u64;
 
@@ -48,7 +50,7 @@ pub trait Tuple: Seal {}
 /// * It must be valid to call `next_unchecked` at least `len` times
 /// * `len <= capacity`
 /// * if `next_unchecked` defers to another `T: TupleElem`, then you should not call `T::next_unchecked` more than once
-///     in your own `next_unchecked`
+///   in your own `next_unchecked`
 #[allow(clippy::len_without_is_empty)]
 pub unsafe trait TupleElem {
     /// The items yielded from this element
@@ -154,7 +156,7 @@ unsafe impl<A: TupleElem> TupleElem for (A,) {
 unsafe impl<A> TupleElem for Vec<A> {
     type Item = A;
     type Data = Input<A>;
-    type Iter = std::vec::IntoIter<A>;
+    type Iter = alloc::vec::IntoIter<A>;
 
     #[inline(always)]
     fn capacity(data: &Self::Data) -> usize {
@@ -204,7 +206,7 @@ unsafe impl<A> TupleElem for Vec<A> {
             }
         }
 
-        std::ptr::drop_in_place(std::slice::from_raw_parts_mut(data.ptr, data.len - len));
+        core::ptr::drop_in_place(core::ptr::slice_from_raw_parts_mut(data.ptr, data.len - len));
     }
 }
 
@@ -274,7 +276,7 @@ unsafe impl<A: TupleElem, T: Seal> Seal for (A, T) {
 
     type Item = (A::Item, T::Item);
     type Data = (A::Data, T::Data);
-    type Iter = std::iter::Zip<A::Iter, T::Iter>;
+    type Iter = core::iter::Zip<A::Iter, T::Iter>;
 
     #[inline]
     fn into_data(self) -> Self::Data {
@@ -300,19 +302,15 @@ unsafe impl<A: TupleElem, T: Seal> Seal for (A, T) {
     fn max_cap<V>((a, rest): &Self::Data, depth: &mut u64) -> Option<usize> {
         let cap_rest = T::max_cap::<V>(rest, depth);
 
-        if A::check_layout::<V>() {
-            let cap = A::capacity(a);
-
-            if let Some(cap_rest) = cap_rest {
-                if cap_rest > cap {
-                    return Some(cap_rest);
-                }
+        match pick_reuse_buffer(&[
+            (A::check_layout::<V>(), A::capacity(a)),
+            (cap_rest.is_some(), cap_rest.unwrap_or(0)),
+        ]) {
+            Some(0) => {
+                *depth = Self::LEN;
+                Some(A::capacity(a))
             }
-
-            *depth = Self::LEN;
-            Some(cap)
-        } else {
-            cap_rest
+            _ => cap_rest,
         }
     }
 
@@ -370,7 +368,7 @@ pub fn try_zip_with_impl<R: Try, In: Tuple>(
     input: In,
     f: impl FnMut(In::Item) -> R,
 ) -> Result<Vec<R::Ok>, R::Error> {
-    if In::check_layout::<R::Ok>() {
+    if can_reuse() && In::check_layout::<R::Ok>() {
         let len = input.remaining_len();
         let mut input = input.into_data();
 
@@ -387,6 +385,53 @@ pub fn try_zip_with_impl<R: Try, In: Tuple>(
     }
 }
 
+/// Zip a tuple of vectors together, as a method instead of the [`zip_with`](crate::zip_with)
+/// macro. This is implemented for tuples of up to 8 vectors
+///
+/// The output will reuse the allocation of one of the vectors in the same way as the macro,
+/// the buffer with the largest capacity out of the ones with the same layout as the output
+/// is picked. The output has the length of the shortest vector, and the remaining elements
+/// of the longer vectors are dropped
+pub trait VecZip: Sized {
+    /// The tuple of elements that is passed to the closure
+    type Item;
+
+    /// Zip the vectors in this tuple together, and combine each tuple of
+    /// elements with `f`
+    fn zip_with<V, F: FnMut(Self::Item) -> V>(self, f: F) -> Vec<V>;
+}
+
+macro_rules! vec_zip {
+    ($($name:ident: $t:ident),+) => {
+        impl<$($t),+> VecZip for ($(Vec<$t>,)+) {
+            type Item = ($($t,)+);
+
+            fn zip_with<V, Func: FnMut(Self::Item) -> V>(self, mut func: Func) -> Vec<V> {
+                use core::convert::Infallible;
+
+                let ($($name,)+) = self;
+
+                match try_zip_with_impl(
+                    crate::list!(WRAP $($name),+),
+                    move |crate::list!(PLACE $($name),+)| Ok::<_, Infallible>(func(($($name,)+))),
+                ) {
+                    Ok(x) => x,
+                    Err(x) => match x {},
+                }
+            }
+        }
+    };
+}
+
+vec_zip!(a: A);
+vec_zip!(a: A, b: B);
+vec_zip!(a: A, b: B, c: C);
+vec_zip!(a: A, b: B, c: C, d: D);
+vec_zip!(a: A, b: B, c: C, d: D, e: E);
+vec_zip!(a: A, b: B, c: C, d: D, e: E, f: F);
+vec_zip!(a: A, b: B, c: C, d: D, e: E, f: F, g: G);
+vec_zip!(a: A, b: B, c: C, d: D, e: E, f: F, g: G, h: H);
+
 impl<V, In: Tuple> ZipWithIter<V, In> {
     pub fn try_into_vec<R: Try<Ok = V>, F: FnMut(In::Item) -> R>(
         mut self,
@@ -433,12 +478,19 @@ impl<V, In: Tuple> Drop for ZipWithIter<V, In> {
             ..
         } = self;
 
+        debug_assert!(remaining_len <= initial_len);
         let initialized_len = initial_len - remaining_len;
 
         defer! {
             if should_free_output {
+                // this is only reached from inside the loop in `try_into_vec`, after
+                // `remaining_len` was decremented, so at least one value was just read
+                debug_assert!(initialized_len != 0);
+
                 unsafe {
-                    Vec::from_raw_parts(output.start, initialized_len - 1, output.cap);
+                    // this saturates so that if no values were read,
+                    // it can't wrap around to a huge length
+                    Vec::from_raw_parts(output.start, initialized_len.saturating_sub(1), output.cap);
                 }
             }
         }
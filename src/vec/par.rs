@@ -0,0 +1,246 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use core::alloc::Layout;
+use core::marker::PhantomData;
+use core::mem::ManuallyDrop;
+use core::sync::atomic::{AtomicU8, Ordering};
+
+use rayon::prelude::*;
+
+use super::{can_reuse, pick_reuse_buffer};
+
+// the states that each chunk of the buffer can be in
+
+// the chunk still holds all of it's inputs
+const INPUT: u8 = 0;
+// the chunk was completely mapped, and holds all of it's outputs
+const OUTPUT: u8 = 1;
+// the chunk panicked, and already cleaned itself up
+const EMPTY: u8 = 2;
+
+/// Does the work of `par_map`
+pub(super) fn par_map<T: Send, U: Send, F: Fn(T) -> U + Sync + Send>(vec: Vec<T>, f: F) -> Vec<U> {
+    if !can_reuse() || Layout::new::<T>() != Layout::new::<U>() {
+        return vec.into_par_iter().map(f).collect();
+    }
+
+    // zipping with zero-sized values doesn't allocate or touch any memory
+    let units = vec![(); vec.len()];
+
+    par_zip_reuse_left(vec, units, move |x, ()| f(x))
+}
+
+/// Does the work of `par_zip_with`
+pub(super) fn par_zip_with<T: Send, U: Send, V: Send, F: Fn(T, U) -> V + Sync + Send>(
+    mut left: Vec<T>,
+    mut right: Vec<U>,
+    f: F,
+) -> Vec<V> {
+    // drop the leftover elements of the longer vector on this thread,
+    // so that both vectors can be split into the same chunks
+    let len = left.len().min(right.len());
+    left.truncate(len);
+    right.truncate(len);
+
+    // this picks the buffer to reuse in the same way as `zip_with`
+    match pick_reuse_buffer(&[
+        (Layout::new::<T>() == Layout::new::<V>(), left.capacity()),
+        (Layout::new::<U>() == Layout::new::<V>(), right.capacity()),
+    ]) {
+        Some(0) => par_zip_reuse_left(left, right, f),
+        Some(_) => par_zip_reuse_left(right, left, move |y, x| f(x, y)),
+        None => left
+            .into_par_iter()
+            .zip(right)
+            .map(move |(x, y)| f(x, y))
+            .collect(),
+    }
+}
+
+// zips two vectors of the same length, and writes the output into the left vector
+fn par_zip_reuse_left<T: Send, U: Send, V: Send, F: Fn(T, U) -> V + Sync + Send>(
+    left: Vec<T>,
+    right: Vec<U>,
+    f: F,
+) -> Vec<V> {
+    debug_assert_eq!(Layout::new::<T>(), Layout::new::<V>());
+    debug_assert_eq!(left.len(), right.len());
+
+    let mut left = ManuallyDrop::new(left);
+    let mut right = ManuallyDrop::new(right);
+    let len = left.len();
+
+    // split the buffer into a few chunks per thread, so that
+    // rayon has some room to balance the work
+    let chunk_len = (len / (4 * rayon::current_num_threads())).max(1);
+    let chunk_count = len.div_ceil(chunk_len);
+    let states = (0..chunk_count)
+        .map(|_| AtomicU8::new(INPUT))
+        .collect::<Vec<_>>();
+
+    let par = ParZip::<T, U, V> {
+        left: left.as_mut_ptr(),
+        right: right.as_mut_ptr(),
+        len,
+        left_cap: left.capacity(),
+        right_cap: right.capacity(),
+        chunk_len,
+        states: &states,
+        drop: PhantomData,
+    };
+
+    (0..chunk_count)
+        .into_par_iter()
+        .for_each(|chunk| unsafe { par.zip_chunk(chunk, &f) });
+
+    // every chunk was mapped, so we don't want to drop anything
+    let par = ManuallyDrop::new(par);
+
+    unsafe {
+        // all of the values in the right vector were moved out
+        Vec::from_raw_parts(par.right, 0, par.right_cap);
+
+        Vec::from_raw_parts(par.left as *mut V, par.len, par.left_cap)
+    }
+}
+
+// This owns the allocations while the chunks are being mapped in parallel,
+// if any of the chunks panic, then this will clean up all of the other chunks
+struct ParZip<'a, T, U, V> {
+    // the output is written over this buffer
+    left: *mut T,
+    right: *mut U,
+    len: usize,
+    left_cap: usize,
+    right_cap: usize,
+
+    // the length of each chunk, the last chunk may be shorter
+    chunk_len: usize,
+
+    // the state of each chunk, one of `INPUT`, `OUTPUT`, or `EMPTY`
+    states: &'a [AtomicU8],
+
+    // for drop check
+    drop: PhantomData<(T, U, V)>,
+}
+
+// Each chunk is only ever accessed by a single thread
+unsafe impl<T: Send, U: Send, V: Send> Sync for ParZip<'_, T, U, V> {}
+
+impl<T, U, V> ParZip<'_, T, U, V> {
+    fn chunk_range(&self, chunk: usize) -> (usize, usize) {
+        let start = chunk * self.chunk_len;
+        let end = self.len.min(start + self.chunk_len);
+        (start, end - start)
+    }
+
+    /// # Safety
+    ///
+    /// This must be called at most once for each chunk
+    unsafe fn zip_chunk<F: Fn(T, U) -> V>(&self, chunk: usize, f: &F) {
+        let (start, len) = self.chunk_range(chunk);
+
+        let mut iter = ChunkIter::<T, U, V> {
+            left: self.left.add(start),
+            right: self.right.add(start),
+            init_len: 0,
+            len,
+            state: &self.states[chunk],
+            drop: PhantomData,
+        };
+
+        // does a pointer walk over this chunk
+        while iter.init_len < iter.len {
+            let left = iter.left.add(iter.init_len);
+            let right = iter.right.add(iter.init_len);
+            let value = f(left.read(), right.read());
+
+            (left as *mut V).write(value);
+            iter.init_len += 1;
+        }
+
+        let iter = ManuallyDrop::new(iter);
+        iter.state.store(OUTPUT, Ordering::Relaxed);
+    }
+}
+
+impl<T, U, V> Drop for ParZip<'_, T, U, V> {
+    fn drop(&mut self) {
+        unsafe {
+            // free the allocations, this happens last
+            defer! {
+                Vec::from_raw_parts(self.left, 0, self.left_cap);
+                Vec::from_raw_parts(self.right, 0, self.right_cap);
+            }
+
+            for (chunk, state) in self.states.iter().enumerate() {
+                let (start, len) = self.chunk_range(chunk);
+                let left = self.left.add(start);
+                let right = self.right.add(start);
+
+                match state.load(Ordering::Relaxed) {
+                    INPUT => {
+                        defer! {
+                            core::ptr::drop_in_place(core::ptr::slice_from_raw_parts_mut(right, len));
+                        }
+
+                        core::ptr::drop_in_place(core::ptr::slice_from_raw_parts_mut(left, len))
+                    }
+                    OUTPUT => core::ptr::drop_in_place(core::ptr::slice_from_raw_parts_mut(
+                        left as *mut V,
+                        len,
+                    )),
+                    _ => (),
+                }
+            }
+        }
+    }
+}
+
+// Maps a single chunk, and cleans it up if the mapping function panics
+struct ChunkIter<'a, T, U, V> {
+    left: *mut T,
+    right: *mut U,
+    init_len: usize,
+    len: usize,
+    state: &'a AtomicU8,
+
+    // for drop check
+    drop: PhantomData<V>,
+}
+
+impl<T, U, V> Drop for ChunkIter<'_, T, U, V> {
+    fn drop(&mut self) {
+        // this is only dropped if `f` panics, right after a pair was read
+        debug_assert!(self.init_len < self.len);
+
+        unsafe {
+            // the chunk is cleaned up, so the `ParZip` shouldn't touch it
+            defer! {
+                self.state.store(EMPTY, Ordering::Relaxed);
+            }
+
+            // destroy the initialized output
+            defer! {
+                core::ptr::drop_in_place(core::ptr::slice_from_raw_parts_mut(
+                    self.left as *mut V,
+                    self.init_len,
+                ));
+            }
+
+            // offset by 1 because the values at `init_len` were
+            // just read from, dropping them would lead to a double free
+            defer! {
+                core::ptr::drop_in_place(core::ptr::slice_from_raw_parts_mut(
+                    self.right.add(self.init_len + 1),
+                    self.len - self.init_len - 1,
+                ));
+            }
+
+            core::ptr::drop_in_place(core::ptr::slice_from_raw_parts_mut(
+                self.left.add(self.init_len + 1),
+                self.len - self.init_len - 1,
+            ));
+        }
+    }
+}
@@ -0,0 +1,62 @@
+use alloc::vec::Vec;
+
+use super::VecExt;
+
+/// A pool of empty vectors, used to recycle allocations across calls to
+/// [`VecExt::map_pooled`](crate::VecExt::map_pooled)
+///
+/// When a map can't reuse the allocation of its input, the output is written into
+/// a vector taken from the pool instead of a new allocation. Once an output isn't
+/// needed anymore, it can be given back to the pool with [`recycle`](VecPool::recycle).
+///
+/// The pool doesn't do any synchronization, so it's meant to be owned by a single thread.
+/// If it needs to be shared between threads, then it should be put behind a lock,
+/// or each thread should get it's own pool
+#[derive(Debug)]
+pub struct VecPool<T> {
+    buffers: Vec<Vec<T>>,
+}
+
+impl<T> Default for VecPool<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> VecPool<T> {
+    /// Creates an empty pool, this doesn't allocate
+    pub const fn new() -> Self {
+        Self {
+            buffers: Vec::new(),
+        }
+    }
+
+    /// The number of vectors in the pool
+    pub fn len(&self) -> usize {
+        self.buffers.len()
+    }
+
+    /// Is the pool empty
+    pub fn is_empty(&self) -> bool {
+        self.buffers.is_empty()
+    }
+
+    /// Takes an empty vector out of the pool, or creates a new one if the pool is empty
+    pub fn take(&mut self) -> Vec<T> {
+        self.buffers.pop().unwrap_or_default()
+    }
+
+    /// Drops all of the values in the vector, and puts it's allocation into the pool
+    ///
+    /// This reuses the allocation in the same cases as
+    /// [`drop_and_reuse`](crate::VecExt::drop_and_reuse),
+    /// if it can't be reused then it's freed instead
+    pub fn recycle<U>(&mut self, vec: Vec<U>) {
+        let vec = vec.drop_and_reuse::<T>();
+
+        // there's nothing to recycle if there is no allocation
+        if vec.capacity() != 0 {
+            self.buffers.push(vec);
+        }
+    }
+}
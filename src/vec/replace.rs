@@ -0,0 +1,58 @@
+use alloc::vec::Vec;
+use core::ops::{Deref, DerefMut};
+
+use super::VecExt;
+
+/// A vector of `U`s that was mapped in place from a borrowed `Vec<T>`
+///
+/// This is created by [`VecExt::replace_map`](crate::VecExt::replace_map).
+/// The borrowed vector can't hold `U`s, so it's left empty while this is alive,
+/// and the mapped values live here instead. This derefs to the `Vec<U>`, so it can
+/// be used like any other vector.
+///
+/// When this is dropped, all of the `U`s are dropped, and the allocation is handed
+/// back to the borrowed vector, in the same way as
+/// [`drop_and_reuse`](crate::VecExt::drop_and_reuse). Use
+/// [`into_inner`](ReplaceMap::into_inner) to keep the `U`s instead
+pub struct ReplaceMap<'a, T, U> {
+    source: &'a mut Vec<T>,
+    vec: Vec<U>,
+}
+
+impl<'a, T, U> ReplaceMap<'a, T, U> {
+    pub(super) fn new(source: &'a mut Vec<T>, vec: Vec<U>) -> Self {
+        debug_assert!(source.is_empty());
+
+        Self { source, vec }
+    }
+
+    /// Take the mapped values out, this leaves the borrowed vector empty,
+    /// without any allocation
+    pub fn into_inner(self) -> Vec<U> {
+        let mut this = core::mem::ManuallyDrop::new(self);
+
+        core::mem::take(&mut this.vec)
+    }
+}
+
+impl<T, U> Deref for ReplaceMap<'_, T, U> {
+    type Target = Vec<U>;
+
+    fn deref(&self) -> &Vec<U> {
+        &self.vec
+    }
+}
+
+impl<T, U> DerefMut for ReplaceMap<'_, T, U> {
+    fn deref_mut(&mut self) -> &mut Vec<U> {
+        &mut self.vec
+    }
+}
+
+impl<T, U> Drop for ReplaceMap<'_, T, U> {
+    fn drop(&mut self) {
+        // if dropping one of the `U`s panics, then the borrowed
+        // vector is left empty, and the allocation is freed
+        *self.source = core::mem::take(&mut self.vec).drop_and_reuse();
+    }
+}
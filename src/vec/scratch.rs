@@ -0,0 +1,125 @@
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+use core::mem::{align_of, size_of, size_of_val};
+use core::ops::{Deref, DerefMut};
+use core::ptr::NonNull;
+
+/// A fixed capacity buffer of `U`s that borrows the spare capacity of a `Vec<T>`
+///
+/// This is created by [`VecExt::with_scratch`](crate::VecExt::with_scratch).
+/// It never reallocates, because the buffer doesn't own the allocation it writes into,
+/// so pushing past the capacity will fail instead of growing the buffer.
+///
+/// All of the values left in the buffer are dropped when the buffer is dropped
+pub struct ScratchVec<'a, U> {
+    ptr: NonNull<U>,
+    len: usize,
+    cap: usize,
+
+    // for drop check, and to borrow the vector for as long as this buffer exists
+    drop: PhantomData<&'a mut [U]>,
+}
+
+impl<'a, U> ScratchVec<'a, U> {
+    pub(super) fn new<T>(vec: &'a mut Vec<T>) -> Self {
+        let spare = vec.spare_capacity_mut();
+
+        // the spare capacity starts right after the last element, so it is aligned
+        // for `T`, and it's also aligned for any `U` with the same or smaller alignment
+        let cap = if align_of::<U>() > align_of::<T>() {
+            0
+        } else if size_of::<U>() == 0 {
+            usize::MAX
+        } else {
+            size_of_val(spare) / size_of::<U>()
+        };
+
+        let ptr = if cap == 0 {
+            NonNull::dangling()
+        } else {
+            unsafe { NonNull::new_unchecked(spare.as_mut_ptr() as *mut U) }
+        };
+
+        Self {
+            ptr,
+            len: 0,
+            cap,
+            drop: PhantomData,
+        }
+    }
+
+    /// The number of values that this buffer can hold
+    pub fn capacity(&self) -> usize {
+        self.cap
+    }
+
+    /// Appends a value to the back of the buffer
+    ///
+    /// # Panics
+    ///
+    /// If the buffer is full
+    pub fn push(&mut self, value: U) {
+        if self.try_push(value).is_err() {
+            panic!(
+                "Tried to push to a full scratch buffer of capacity {}",
+                self.cap
+            )
+        }
+    }
+
+    /// Appends a value to the back of the buffer, or gives the
+    /// value back if the buffer is full
+    pub fn try_push(&mut self, value: U) -> Result<(), U> {
+        if self.len == self.cap {
+            return Err(value);
+        }
+
+        unsafe {
+            self.ptr.as_ptr().add(self.len).write(value);
+        }
+
+        self.len += 1;
+
+        Ok(())
+    }
+
+    /// Removes the last value in the buffer
+    pub fn pop(&mut self) -> Option<U> {
+        self.len = self.len.checked_sub(1)?;
+
+        unsafe { Some(self.ptr.as_ptr().add(self.len).read()) }
+    }
+
+    /// Drops all of the values in the buffer
+    pub fn clear(&mut self) {
+        let len = self.len;
+
+        // set the length first, so that if a destructor panics
+        // the values aren't dropped again
+        self.len = 0;
+
+        unsafe {
+            core::ptr::drop_in_place(core::ptr::slice_from_raw_parts_mut(self.ptr.as_ptr(), len));
+        }
+    }
+}
+
+impl<U> Deref for ScratchVec<'_, U> {
+    type Target = [U];
+
+    fn deref(&self) -> &[U] {
+        unsafe { core::slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl<U> DerefMut for ScratchVec<'_, U> {
+    fn deref_mut(&mut self) -> &mut [U] {
+        unsafe { core::slice::from_raw_parts_mut(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl<U> Drop for ScratchVec<'_, U> {
+    fn drop(&mut self) {
+        self.clear()
+    }
+}
@@ -0,0 +1,68 @@
+use alloc::vec::Vec;
+use core::alloc::Layout;
+use core::mem::ManuallyDrop;
+use core::simd::{Simd, SimdElement};
+
+use super::can_reuse;
+
+/// Does the work of `map_simd`
+pub(super) fn map_simd<T, U, const LANES: usize, F>(mut vec: Vec<T>, mut f: F) -> Vec<U>
+where
+    T: SimdElement + Default,
+    U: SimdElement,
+    F: FnMut(Simd<T, LANES>) -> Simd<U, LANES>,
+{
+    if !can_reuse() || Layout::new::<T>() != Layout::new::<U>() {
+        let mut out = Vec::with_capacity(vec.len());
+        let mut chunks = vec.chunks_exact(LANES);
+
+        for chunk in chunks.by_ref() {
+            out.extend_from_slice(f(Simd::from_slice(chunk)).as_array());
+        }
+
+        let rest = chunks.remainder();
+
+        if !rest.is_empty() {
+            out.extend_from_slice(&f(Simd::load_or_default(rest))[..rest.len()]);
+        }
+
+        return out;
+    }
+
+    let len = vec.len();
+    let ptr = vec.as_mut_ptr();
+
+    // `SimdElement`s are always `Copy`, so there is nothing to drop if `f`
+    // panics, the vector only needs to free it's allocation
+    unsafe { vec.set_len(0) }
+
+    let mut init_len = 0;
+
+    // the vector is only aligned for `T`, and `Simd` may have a larger
+    // alignment, so the lanes are read and written unaligned
+    while init_len + LANES <= len {
+        unsafe {
+            let slot = ptr.add(init_len);
+            let value = f(slot.cast::<Simd<T, LANES>>().read_unaligned());
+
+            slot.cast::<Simd<U, LANES>>().write_unaligned(value);
+            init_len += LANES;
+        }
+    }
+
+    // the last partial chunk is padded with `T::default()`,
+    // and the padding lanes of the output are ignored
+    if init_len < len {
+        unsafe {
+            let slot = ptr.add(init_len);
+            let rest = core::slice::from_raw_parts(slot, len - init_len);
+            let value = f(Simd::load_or_default(rest));
+
+            core::ptr::copy_nonoverlapping(value.as_array().as_ptr(), slot as *mut U, rest.len());
+        }
+    }
+
+    let mut vec = ManuallyDrop::new(vec);
+
+    unsafe { Vec::from_raw_parts(vec.as_mut_ptr() as *mut U, len, vec.capacity()) }
+}
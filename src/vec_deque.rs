@@ -0,0 +1,89 @@
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+
+use super::{Try, VecExt};
+
+/// Extension methods for `VecDeque<T>`
+///
+/// These convert the `VecDeque<T>` to a `Vec<T>`, which doesn't reallocate, but
+/// may move the elements around inside of the allocation to make them contiguous.
+/// So the results will always be in the same logical order as the input.
+pub trait VecDequeExt: Sized {
+    /// The type that the `VecDeque<T>` stores
+    type T;
+
+    /// Map a deque to another deque, will try and reuse the allocation if the
+    /// allocation layouts of the two types match, i.e. if
+    /// `std::alloc::Layout::<T>::new() == std::alloc::Layout::<U>::new()`
+    /// then the allocation will be reused
+    fn map<U, F: FnMut(Self::T) -> U>(self, mut f: F) -> VecDeque<U> {
+        use core::convert::Infallible;
+
+        match self.try_map(move |x| Ok::<_, Infallible>(f(x))) {
+            Ok(x) => x,
+            Err(x) => match x {},
+        }
+    }
+
+    /// Map a deque to another deque, will try and reuse the allocation if the
+    /// allocation layouts of the two types match, i.e. if
+    /// `std::alloc::Layout::<T>::new() == std::alloc::Layout::<U>::new()`
+    /// then the allocation will be reused
+    ///
+    /// The mapping function can be fallible, and on early return, it will drop all previous values,
+    /// and the rest of the input deque. The error will be returned as a `Result`
+    fn try_map<U, R: Try<Ok = U>, F: FnMut(Self::T) -> R>(
+        self,
+        f: F,
+    ) -> Result<VecDeque<U>, R::Error>;
+
+    /// Zip a deque to another deque and combine them, the result will be returned,
+    /// the allocation will be reused if possible, in the same way as
+    /// [`VecExt::zip_with`](trait.VecExt.html#method.zip_with)
+    fn zip_with<U, V, F: FnMut(Self::T, U) -> V>(
+        self,
+        other: VecDeque<U>,
+        mut f: F,
+    ) -> VecDeque<V> {
+        use core::convert::Infallible;
+
+        match self.try_zip_with(other, move |x, y| Ok::<_, Infallible>(f(x, y))) {
+            Ok(x) => x,
+            Err(x) => match x {},
+        }
+    }
+
+    /// Zip a deque to another deque and combine them, the result will be returned,
+    /// the allocation will be reused if possible, in the same way as
+    /// [`VecExt::zip_with`](trait.VecExt.html#method.zip_with)
+    ///
+    /// The mapping function can be fallible, and on early return, it will drop all previous values,
+    /// and the rest of the input deques. The error will be returned as a `Result`
+    fn try_zip_with<U, V, R: Try<Ok = V>, F: FnMut(Self::T, U) -> R>(
+        self,
+        other: VecDeque<U>,
+        f: F,
+    ) -> Result<VecDeque<V>, R::Error>;
+}
+
+impl<T> VecDequeExt for VecDeque<T> {
+    type T = T;
+
+    fn try_map<U, R: Try<Ok = U>, F: FnMut(Self::T) -> R>(
+        self,
+        f: F,
+    ) -> Result<VecDeque<U>, R::Error> {
+        // converting between `VecDeque<T>` and `Vec<T>` never reallocates
+        Vec::from(self).try_map(f).map(VecDeque::from)
+    }
+
+    fn try_zip_with<U, V, R: Try<Ok = V>, F: FnMut(Self::T, U) -> R>(
+        self,
+        other: VecDeque<U>,
+        f: F,
+    ) -> Result<VecDeque<V>, R::Error> {
+        Vec::from(self)
+            .try_zip_with(Vec::from(other), f)
+            .map(VecDeque::from)
+    }
+}
@@ -0,0 +1,159 @@
+#![cfg(feature = "allocator_api")]
+#![feature(allocator_api)]
+
+use std::alloc::{AllocError, Allocator, Global, Layout};
+use std::cell::Cell;
+use std::ptr::NonNull;
+use std::rc::Rc;
+
+use vec_utils::VecInExt;
+
+#[macro_use]
+mod common;
+
+// counts the number of live allocations
+#[derive(Clone, Default)]
+struct Counting(Rc<Cell<isize>>);
+
+unsafe impl Allocator for Counting {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.0.set(self.0.get() + 1);
+        Global.allocate(layout)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        self.0.set(self.0.get() - 1);
+        Global.deallocate(ptr, layout)
+    }
+}
+
+// fails every allocation once it's turned off
+#[derive(Clone, Default)]
+struct Failing(Rc<Cell<bool>>);
+
+unsafe impl Allocator for Failing {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        if self.0.get() {
+            Err(AllocError)
+        } else {
+            Global.allocate(layout)
+        }
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        Global.deallocate(ptr, layout)
+    }
+}
+
+fn counting_vec<T>(alloc: &Counting, values: impl IntoIterator<Item = T>) -> Vec<T, Counting> {
+    let mut vec = Vec::new_in(alloc.clone());
+    vec.extend(values);
+    vec
+}
+
+#[test]
+fn map_in() {
+    let alloc = Counting::default();
+
+    let vec = counting_vec(&alloc, 0u32..10);
+    let ptr = vec.as_ptr() as usize;
+
+    let vec = vec.map_in(|x| x as i32 * 2);
+    assert_eq!(vec, [0, 2, 4, 6, 8, 10, 12, 14, 16, 18]);
+    assert_reuse!(vec.as_ptr() as usize, ptr);
+    assert_eq!(alloc.0.get(), 1);
+
+    let vec = vec.map_in(f64::from);
+    assert_eq!(vec[3], 6.0);
+    assert_eq!(alloc.0.get(), 1);
+
+    drop(vec);
+    assert_eq!(alloc.0.get(), 0);
+}
+
+#[test]
+fn try_map_in() {
+    let alloc = Counting::default();
+
+    let vec = counting_vec(&alloc, vec![String::from("a"); 10]);
+    let res = vec.try_map_in(|x| if x.len() == 1 { Err(x) } else { Ok(x) });
+    assert_eq!(res.unwrap_err(), "a");
+    assert_eq!(alloc.0.get(), 0);
+
+    let vec = counting_vec(&alloc, 0u32..10);
+    let res = vec.try_map_in(|x| if x < 10 { Some(x as u64) } else { None });
+    assert_eq!(res.ok().map(|vec| vec.len()), Some(10));
+    assert_eq!(alloc.0.get(), 0);
+}
+
+#[test]
+fn zip_with_in() {
+    let alloc = Counting::default();
+
+    let a = counting_vec(&alloc, (0u32..10).map(|x| x.to_string()));
+    let b = (0u32..5).map(|x| x.to_string()).collect::<Vec<_>>();
+    let ptr = a.as_ptr() as usize;
+
+    let vec = a.zip_with_in(b, |x, y| x + &y);
+    assert_eq!(vec, ["00", "11", "22", "33", "44"]);
+    assert_reuse!(vec.as_ptr() as usize, ptr);
+    assert_eq!(alloc.0.get(), 1);
+
+    drop(vec);
+    assert_eq!(alloc.0.get(), 0);
+}
+
+#[test]
+fn map_in_panic() {
+    let alloc = Counting::default();
+
+    let vec = counting_vec(&alloc, (0u32..10).map(|x| x.to_string()));
+
+    let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        vec.map_in(|x| {
+            if x == "5" {
+                panic!("stop");
+            }
+
+            x + "!"
+        })
+    }));
+
+    assert!(res.is_err());
+    assert_eq!(alloc.0.get(), 0);
+}
+
+#[test]
+fn try_map_alloc_in() {
+    let alloc = Counting::default();
+
+    let vec = counting_vec(&alloc, 0u32..10);
+    let vec = vec.try_map_alloc_in(|x| x as u64).unwrap();
+    assert_eq!(vec[9], 9);
+    assert_eq!(alloc.0.get(), 1);
+
+    drop(vec);
+    assert_eq!(alloc.0.get(), 0);
+
+    let failing = Failing::default();
+    let mut vec = Vec::new_in(failing.clone());
+    vec.extend((0u32..10).map(|x| x.to_string()));
+    failing.0.set(true);
+
+    // the output can't be allocated, so the error is returned instead of aborting
+    let res = vec.try_map_alloc_in(|x| x.len() as u16);
+    assert_eq!(res.unwrap_err(), AllocError);
+
+    // the allocation is reused, so this doesn't need to allocate
+    if cfg!(not(feature = "disable-reuse")) {
+        failing.0.set(false);
+        let mut vec = Vec::new_in(failing.clone());
+        vec.extend(0u32..10);
+        let ptr = vec.as_ptr() as usize;
+        failing.0.set(true);
+
+        let vec = vec.try_map_alloc_in(|x| x as f32).unwrap();
+        assert_eq!(vec[3], 3.0);
+        assert_eq!(vec.as_ptr() as usize, ptr);
+    }
+}
@@ -0,0 +1,47 @@
+#![cfg(feature = "bumpalo")]
+
+use bumpalo::Bump;
+use vec_utils::VecExt;
+
+#[test]
+fn map_in_bump() {
+    let bump = Bump::new();
+
+    let vec = vec![1u8, 2, 3].map_in_bump(&bump, u64::from);
+    assert_eq!(*vec, [1, 2, 3]);
+
+    // the output lives in the arena, even if it could have reused the input
+    let input = vec![1.0f32, 2.0];
+    let ptr = input.as_ptr() as usize;
+    let vec = input.map_in_bump(&bump, f32::to_bits);
+    assert_eq!(*vec, [1.0f32.to_bits(), 2.0f32.to_bits()]);
+    assert_ne!(vec.as_ptr() as usize, ptr);
+    assert!(std::ptr::eq(vec.bump(), &bump));
+}
+
+#[test]
+fn map_in_bump_panic() {
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+    use std::rc::Rc;
+
+    let bump = Bump::new();
+    let counter = Rc::new(());
+
+    let vec = (0..10).map(|_| counter.clone()).collect::<Vec<_>>();
+
+    let res = catch_unwind(AssertUnwindSafe(|| {
+        let mut i = 0;
+        vec.map_in_bump(&bump, |x| {
+            i += 1;
+
+            if i == 5 {
+                panic!("stop");
+            }
+
+            x
+        })
+    }));
+
+    assert!(res.is_err());
+    assert_eq!(Rc::strong_count(&counter), 1);
+}
@@ -0,0 +1,8 @@
+// the allocation is never reused with the `disable-reuse` feature
+macro_rules! assert_reuse {
+    ($($args:tt)*) => {
+        if cfg!(not(feature = "disable-reuse")) {
+            assert_eq!($($args)*);
+        }
+    };
+}
@@ -36,8 +36,48 @@ mod drop_counter {
 
         pub fn create<T: Debug + Any>(&self, value: T) -> OnDrop<'_, T> {
             let len = self.init();
-            OnDrop(&self, len, TypeId::of::<T>(), value)
+            OnDrop(self, len, TypeId::of::<T>(), value)
         }
+
+        // the number of values that were created by this counter
+        pub fn created(&self) -> usize {
+            self.0.read().unwrap().len()
+        }
+    }
+
+    // panics if `x` holds `stop`, so that a mapping function can stop partway through
+    pub fn stop_at<T: Debug + Any + PartialEq>(x: &OnDrop<'_, T>, stop: T) {
+        if *x.get() == stop {
+            panic!("stop");
+        }
+    }
+
+    // runs `run`, and checks that it panicked
+    pub fn assert_panics<R>(run: impl FnOnce() -> R) {
+        let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(run));
+        assert!(res.is_err(), "expected a panic");
+    }
+
+    // gives `run` a mapping function that maps each value with `map`, and panics once
+    // it reaches `stop`. This checks that `run` panicked, and returns the number
+    // of values that were mapped before the panic
+    pub fn panics_at<'a, U: Debug + Any, R>(
+        dr: &'a DropCounter,
+        stop: i32,
+        mut map: impl FnMut(i32) -> U,
+        run: impl FnOnce(&mut dyn FnMut(OnDrop<'a, i32>) -> OnDrop<'a, U>) -> R,
+    ) -> usize {
+        let mut mapped = 0;
+
+        assert_panics(|| {
+            run(&mut |x| {
+                stop_at(&x, stop);
+                mapped += 1;
+                dr.create(map(*x.get()))
+            })
+        });
+
+        mapped
     }
 
     impl<T: Debug + Any> OnDrop<'_, T> {
@@ -137,7 +177,7 @@ mod drop_counter {
     }
 }
 
-use drop_counter::DropCounter;
+use drop_counter::{assert_panics, panics_at, stop_at, DropCounter};
 
 mod boxed {
     use super::*;
@@ -183,6 +223,144 @@ mod boxed {
     }
 }
 
+mod slice {
+    use super::*;
+
+    #[test]
+    fn try_map_to_vec() {
+        let dr = DropCounter::new();
+
+        let slice = (0..10).map(|x| dr.create(x)).collect::<Vec<_>>();
+
+        let err = slice
+            .try_map_to_vec(|x| if *x.get() == 5 { None } else { Some(x.clone()) })
+            .is_err();
+
+        assert!(err);
+    }
+}
+
+mod box_slice {
+    use super::*;
+
+    #[test]
+    fn map() {
+        let dr = DropCounter::new();
+
+        let bx = (0..10).map(|x| dr.create(x)).collect::<Box<[_]>>();
+
+        BoxSliceExt::map(bx, |x| dr.create(*x.get()));
+    }
+
+    #[test]
+    fn try_zip_with() {
+        let dr = DropCounter::new();
+
+        let a = (0..10).map(|x| dr.create(x)).collect::<Box<[_]>>();
+        let b = (20..40).map(|x| dr.create(x)).collect::<Box<[_]>>();
+
+        let mut counter = 0;
+
+        let err = a
+            .try_zip_with(b, |x, y| {
+                counter += 1;
+
+                if counter == 5 {
+                    None
+                } else {
+                    Some(dr.create(x.get() + y.get()))
+                }
+            })
+            .is_err();
+
+        assert!(err);
+    }
+}
+
+// these always give back the original slice when reuse is disabled, so nothing is mapped
+#[cfg(not(feature = "disable-reuse"))]
+mod rc {
+    use super::*;
+    use std::rc::Rc;
+    use std::sync::Arc;
+
+    #[test]
+    fn map_rc() {
+        let dr = DropCounter::new();
+
+        let rc = (0..10).map(|x| dr.create(x)).collect::<Rc<[_]>>();
+
+        let rc = try_map_rc(rc, |x| dr.create(*x.get() + 10)).ok().unwrap();
+        assert_eq!(
+            rc.iter().map(|x| *x.get()).collect::<Vec<_>>(),
+            (10..20).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn map_rc_panic() {
+        let dr = DropCounter::new();
+
+        let rc = (0..10).map(|x| dr.create(x)).collect::<Rc<[_]>>();
+
+        let mapped = panics_at(&dr, 5, |x| x + 10, |f| try_map_rc(rc, f));
+        assert_eq!(mapped, 5);
+    }
+
+    #[test]
+    fn map_arc_panic() {
+        let dr = DropCounter::new();
+
+        let arc = (0..10).map(|x| dr.create(x)).collect::<Arc<[_]>>();
+
+        let mapped = panics_at(&dr, 5, |x| x + 10, |f| try_map_arc(arc, f));
+        assert_eq!(mapped, 5);
+    }
+}
+
+mod array {
+    use super::*;
+    use vec_utils::ArrayExt;
+
+    #[test]
+    fn map_array() {
+        let dr = DropCounter::new();
+
+        let array = [0, 1, 2, 3, 4].map(|x| dr.create(x));
+        let array = array.map_array(|x| dr.create(*x.get() as f32));
+        let array = array.map_array(|x| dr.create(*x.get() as u8));
+
+        assert_eq!(*array[4].get(), 4);
+    }
+
+    #[test]
+    fn map_array_panic() {
+        let dr = DropCounter::new();
+
+        let array = [0, 1, 2, 3, 4].map(|x| dr.create(x));
+
+        let mapped = panics_at(&dr, 2, |x| x as f32, |f| array.map_array(f));
+        assert_eq!(mapped, 2);
+    }
+
+    #[test]
+    fn try_map_array() {
+        let dr = DropCounter::new();
+
+        let array = [0, 1, 2, 3, 4].map(|x| dr.create(x));
+
+        let res = array.try_map_array(|x| {
+            if *x.get() == 3 {
+                None
+            } else {
+                Some(dr.create(*x.get() as u8))
+            }
+        });
+
+        assert!(res.is_err());
+    }
+}
+
 mod vec {
     #![allow(unused_assignments)]
     use super::*;
@@ -220,107 +398,1768 @@ mod vec {
     }
 
     #[test]
-    fn zip_with_same() {
+    fn map_smaller() {
         let dr = DropCounter::new();
 
-        let mut a = (0i32..10).map(|x| dr.create(x)).collect::<Vec<_>>();
-        let b = (20i32..30).map(|x| dr.create(x)).collect::<Vec<_>>();
+        let vec = (0..10).map(|x| dr.create([x; 4])).collect::<Vec<_>>();
 
-        let mut flip = false;
+        vec.map(|x| dr.create(x.get()[0]));
+    }
 
-        a = a.zip_with(b, |x, y| {
-            flip = !flip;
+    #[test]
+    fn try_map_smaller() {
+        let dr = DropCounter::new();
 
-            if flip {
-                x
-            } else {
-                y
-            }
-        });
+        let vec = (0..10).map(|x| dr.create([x; 4])).collect::<Vec<_>>();
+
+        let mut counter = 0;
+
+        let err = vec
+            .try_map(|x| {
+                counter += 1;
+
+                if counter == 3 {
+                    None
+                } else {
+                    Some(dr.create(x.get()[0]))
+                }
+            })
+            .is_err();
+
+        assert!(err);
     }
 
     #[test]
-    fn zip_with_diff() {
+    fn map_larger() {
         let dr = DropCounter::new();
 
-        let a = (0..10).map(|x| dr.create(x)).collect::<Vec<_>>();
-        let mut b = (20..40).map(|x| dr.create(x)).collect::<Vec<_>>();
+        let mut vec = Vec::with_capacity(40);
+        vec.extend((0..10).map(|x| dr.create(x)));
 
-        let mut flip = false;
+        vec.map(|x| dr.create([*x.get(); 4]));
+    }
 
-        b = a.zip_with(b, |x, y| {
-            flip = !flip;
+    #[test]
+    fn try_map_larger() {
+        let dr = DropCounter::new();
 
-            if flip {
-                x
+        let mut vec = Vec::with_capacity(40);
+        vec.extend((0..10).map(|x| dr.create(x)));
+
+        let mut counter = 0;
+
+        let err = vec
+            .try_map(|x| {
+                counter += 1;
+
+                if counter == 3 {
+                    None
+                } else {
+                    Some(dr.create([*x.get(); 4]))
+                }
+            })
+            .is_err();
+
+        assert!(err);
+    }
+
+    #[test]
+    fn filter_map() {
+        let dr = DropCounter::new();
+
+        let vec = (0..10).map(|x| dr.create(x)).collect::<Vec<_>>();
+
+        vec.filter_map(|x| {
+            if x.get() % 3 == 0 {
+                Some(dr.create(*x.get()))
             } else {
-                y
+                None
             }
         });
     }
 
     #[test]
-    fn try_zip_with() {
+    fn filter_map_panic() {
         let dr = DropCounter::new();
 
-        let a = (0..10).map(|x| dr.create(x)).collect::<Vec<_>>();
-        let b = (20..40).map(|x| dr.create(x)).collect::<Vec<_>>();
-
-        let mut flip = false;
-        let mut counter = 0;
+        let vec = (0..10).map(|x| dr.create(x)).collect::<Vec<_>>();
 
-        let err = a
-            .try_zip_with(b, |x, y| {
-                flip = !flip;
-                counter += 1;
+        assert_panics(|| {
+            vec.filter_map(|x| {
+                stop_at(&x, 7);
 
-                if counter == 5 {
-                    None
+                if x.get() % 3 == 0 {
+                    Some(dr.create(*x.get()))
                 } else {
-                    Some(dr.create((*x.get()) as f32 + *y.get() as f32))
+                    None
                 }
             })
+        });
+
+        // only 0, 3, and 6 were kept before the panic
+        assert_eq!(dr.created(), 10 + 3);
+    }
+
+    #[test]
+    fn try_filter_map() {
+        let dr = DropCounter::new();
+
+        let vec = (0..10).map(|x| dr.create(x)).collect::<Vec<_>>();
+
+        let err = vec
+            .try_filter_map(|x| match *x.get() {
+                7 => Err(()),
+                x if x % 3 == 0 => Ok(Some(dr.create(x))),
+                _ => Ok(None),
+            })
             .is_err();
 
         assert!(err);
     }
-}
 
-mod tuple {
-    #![allow(unused_assignments)]
-    use super::*;
-    use crate::{try_zip_with, zip_with};
+    #[test]
+    fn unzip_with_panic() {
+        let dr = DropCounter::new();
+
+        let vec = (0..10).map(|x| dr.create(x)).collect::<Vec<_>>();
+
+        assert_panics(|| {
+            vec.unzip_with(|x| {
+                stop_at(&x, 4);
+
+                (dr.create(*x.get()), dr.create(*x.get() as f32))
+            })
+        });
+
+        // a pair of outputs for each of 0..4
+        assert_eq!(dr.created(), 10 + 2 * 4);
+    }
 
     #[test]
-    fn map() {
+    fn partition_map_panic() {
+        use vec_utils::Either;
+
         let dr = DropCounter::new();
 
         let vec = (0..10).map(|x| dr.create(x)).collect::<Vec<_>>();
 
-        zip_with!(vec, |x| dr.create(*x.get()));
+        assert_panics(|| {
+            vec.partition_map(|x| {
+                stop_at(&x, 7);
+
+                let x = *x.get();
+
+                if x % 2 == 0 {
+                    Either::Left(dr.create(x))
+                } else {
+                    Either::Right(dr.create(x as f32))
+                }
+            })
+        });
+
+        assert_eq!(dr.created(), 10 + 7);
     }
 
     #[test]
-    fn try_map() {
+    fn flat_map() {
         let dr = DropCounter::new();
 
         let vec = (0..10).map(|x| dr.create(x)).collect::<Vec<_>>();
 
-        let mut counter = 0;
+        let vec = vec.flat_map(|x| {
+            let x = *x.get();
+            (0..x % 4).map(|_| dr.create(x)).collect::<Vec<_>>()
+        });
 
-        let err = try_zip_with!(vec, |x| {
-            counter += 1;
+        assert_eq!(vec.len(), 13);
+    }
 
-            if counter == 3 {
+    #[test]
+    fn flat_map_panic() {
+        let dr = DropCounter::new();
+
+        let vec = (0..10).map(|x| dr.create(x)).collect::<Vec<_>>();
+
+        assert_panics(|| {
+            vec.flat_map(|x| {
+                stop_at(&x, 4);
+
+                let x = *x.get();
+                (0..x % 3).map(|_| dr.create(x)).collect::<Vec<_>>()
+            })
+        });
+
+        // 0, 1, 2, and 3 expand to 0, 1, 2, and 0 values
+        assert_eq!(dr.created(), 10 + 3);
+    }
+
+    #[test]
+    fn flat_map_iter_panic() {
+        let dr = DropCounter::new();
+
+        let vec = (0..10).map(|x| dr.create(x)).collect::<Vec<_>>();
+
+        assert_panics(|| {
+            let dr = &dr;
+
+            vec.flat_map(|x| {
+                let x = *x.get();
+
+                (0..2).map(move |i| {
+                    if x == 6 && i == 1 {
+                        panic!("stop");
+                    }
+
+                    dr.create(x)
+                })
+            })
+        });
+
+        // the panic happens halfway through the iterator for 6
+        assert_eq!(dr.created(), 10 + 2 * 6 + 1);
+    }
+
+    #[test]
+    fn map_while() {
+        let dr = DropCounter::new();
+
+        let vec = (0..10).map(|x| dr.create(x)).collect::<Vec<_>>();
+
+        let vec = vec.map_while(|x| {
+            if *x.get() == 5 {
                 None
             } else {
-                Some(dr.create(*x.get() as f32))
+                Some(dr.create(*x.get()))
+            }
+        });
+
+        assert_eq!(vec.len(), 5);
+    }
+
+    #[test]
+    fn map_while_panic() {
+        let dr = DropCounter::new();
+
+        let vec = (0..10).map(|x| dr.create(x)).collect::<Vec<_>>();
+
+        assert_panics(|| {
+            vec.map_while(|x| {
+                stop_at(&x, 5);
+
+                Some(dr.create(*x.get()))
+            })
+        });
+
+        assert_eq!(dr.created(), 10 + 5);
+    }
+
+    #[test]
+    fn zip_with_iter() {
+        let dr = DropCounter::new();
+
+        let vec = (0..10).map(|x| dr.create(x)).collect::<Vec<_>>();
+
+        let vec = vec.zip_with_iter(0..5, |x, y| dr.create(*x.get() + y));
+
+        assert_eq!(vec.len(), 5);
+    }
+
+    #[test]
+    fn zip_with_iter_panic() {
+        let dr = DropCounter::new();
+
+        let vec = (0..10).map(|x| dr.create(x)).collect::<Vec<_>>();
+
+        assert_panics(|| {
+            vec.zip_with_iter(0.., |x, y| {
+                if y == 5 {
+                    panic!("stop");
+                }
+
+                dr.create(*x.get() + y)
+            })
+        });
+
+        assert_eq!(dr.created(), 10 + 5);
+    }
+
+    #[test]
+    fn map_into_slice() {
+        let dr = DropCounter::new();
+
+        let mut out = (0..10).map(|x| dr.create(x)).collect::<Vec<_>>();
+        let vec = (0..10).map(|x| dr.create(x)).collect::<Vec<_>>();
+
+        let mapped = panics_at(&dr, 5, |x| x + 10, |f| vec.map_into_slice(&mut out, f));
+
+        // the slots that weren't written to still hold their old values
+        assert_eq!(mapped, 5);
+        assert_eq!(*out[4].get(), 14);
+        assert_eq!(*out[5].get(), 5);
+    }
+
+    #[test]
+    fn map_into_uninit() {
+        let dr = DropCounter::new();
+
+        let mut out = (0..10)
+            .map(|_| std::mem::MaybeUninit::uninit())
+            .collect::<Vec<_>>();
+        let vec = (0..5).map(|x| dr.create(x)).collect::<Vec<_>>();
+
+        let init = vec.map_into_uninit(&mut out, |x| dr.create(*x.get() + 10));
+
+        assert_eq!(init.len(), 5);
+        assert_eq!(*init[4].get(), 14);
+
+        unsafe {
+            std::ptr::drop_in_place(init);
+        }
+    }
+
+    #[test]
+    fn map_into_uninit_panic() {
+        let dr = DropCounter::new();
+
+        let mut out = (0..10)
+            .map(|_| std::mem::MaybeUninit::uninit())
+            .collect::<Vec<_>>();
+        let vec = (0..10).map(|x| dr.create(x)).collect::<Vec<_>>();
+
+        let mapped = panics_at(&dr, 5, |x| x + 10, |f| vec.map_into_uninit(&mut out, f));
+        assert_eq!(mapped, 5);
+    }
+
+    #[test]
+    fn map_nested_panic() {
+        let dr = DropCounter::new();
+
+        let vec = (0..4)
+            .map(|i| (0..4).map(|x| dr.create(i * 4 + x)).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+
+        assert_panics(|| {
+            vec.map_nested(|x| {
+                stop_at(&x, 9);
+
+                dr.create(*x.get() + 16)
+            })
+        });
+
+        // the panic happens partway through the third inner vector
+        assert_eq!(dr.created(), 16 + 9);
+    }
+
+    #[test]
+    fn interleave() {
+        let dr = DropCounter::new();
+
+        let a = (0..3).map(|x| dr.create(x * 2)).collect::<Vec<_>>();
+        let mut b = Vec::with_capacity(10);
+        b.extend((0..5).map(|x| dr.create(x * 2 + 1)));
+
+        let vec = a.interleave(b);
+
+        assert_eq!(
+            vec.iter().map(|x| *x.get()).collect::<Vec<_>>(),
+            [0, 1, 2, 3, 4, 5, 7, 9]
+        );
+    }
+
+    #[test]
+    fn consume_fold() {
+        let dr = DropCounter::new();
+
+        let vec = (0..10).map(|x| dr.create(x)).collect::<Vec<_>>();
+
+        let sum = vec.consume_fold(0, |acc, x| acc + *x.get());
+
+        assert_eq!(sum, 45);
+    }
+
+    #[test]
+    fn consume_fold_panic() {
+        let dr = DropCounter::new();
+
+        let vec = (0..10).map(|x| dr.create(x)).collect::<Vec<_>>();
+        let mut folded = 0;
+
+        assert_panics(|| {
+            vec.consume_fold(0, |acc, x| {
+                stop_at(&x, 5);
+                folded += 1;
+
+                acc + *x.get()
+            })
+        });
+
+        assert_eq!(folded, 5);
+    }
+
+    #[test]
+    fn try_reduce() {
+        let dr = DropCounter::new();
+
+        let vec = (0..10).map(|x| dr.create(x)).collect::<Vec<_>>();
+
+        let res = vec.try_reduce(|x, y| {
+            if *y.get() == 5 {
+                Err(y)
+            } else {
+                Ok(dr.create(*x.get() + *y.get()))
+            }
+        });
+
+        assert!(res.is_err_and(|x| *x.get() == 5));
+    }
+
+    #[test]
+    fn zip_with_array() {
+        let dr = DropCounter::new();
+
+        let vec = (0..10).map(|x| dr.create(x)).collect::<Vec<_>>();
+        let array = [dr.create(0), dr.create(1), dr.create(2)];
+        let vec = vec.zip_with_array(array, |x, y| dr.create(*x.get() + *y.get()));
+        assert_eq!(vec.len(), 3);
+
+        let vec = (0..2).map(|x| dr.create(x)).collect::<Vec<_>>();
+        let array = [dr.create(0), dr.create(1), dr.create(2)];
+        let vec = vec.zip_with_array(array, |x, y| dr.create(*x.get() + *y.get()));
+        assert_eq!(vec.len(), 2);
+    }
+
+    #[test]
+    fn map_rev() {
+        let dr = DropCounter::new();
+
+        let vec = (0..10).map(|x| dr.create(x)).collect::<Vec<_>>();
+
+        let vec = vec.map_rev(|x| dr.create(*x.get() + 10));
+
+        assert_eq!(*vec[0].get(), 10);
+    }
+
+    #[test]
+    fn map_rev_panic() {
+        let dr = DropCounter::new();
+
+        let vec = (0..10).map(|x| dr.create(x)).collect::<Vec<_>>();
+
+        // the values are mapped from the back, so only 9, 8, 7, and 6 are mapped
+        let mapped = panics_at(&dr, 5, |x| x + 10, |f| vec.map_rev(f));
+        assert_eq!(mapped, 4);
+    }
+
+    #[test]
+    fn zip_with_mut() {
+        let dr = DropCounter::new();
+
+        let mut vec = (0..10).map(|x| dr.create(x)).collect::<Vec<_>>();
+        let other = (0..5).map(|x| dr.create(x)).collect::<Vec<_>>();
+
+        vec.zip_with_mut(other, |x, y| *x = dr.create(*x.get() + *y.get()));
+
+        assert_eq!(vec.len(), 5);
+        assert_eq!(*vec[4].get(), 8);
+    }
+
+    #[test]
+    fn map_chunks_exact() {
+        let dr = DropCounter::new();
+
+        let vec = (0..10).map(|x| dr.create(x)).collect::<Vec<_>>();
+
+        vec.map_chunks_exact::<3, _>(|chunk| {
+            chunk[0] = dr.create(*chunk[0].get() + 10);
+        });
+    }
+
+    #[test]
+    fn map_chunks_exact_panic() {
+        let dr = DropCounter::new();
+
+        let vec = (0..10).map(|x| dr.create(x)).collect::<Vec<_>>();
+
+        assert_panics(|| {
+            vec.map_chunks_exact::<3, _>(|chunk| {
+                stop_at(&chunk[0], 6);
+
+                chunk[0] = dr.create(*chunk[0].get() + 10);
+            })
+        });
+
+        // only the chunks starting at 0 and 3 were mapped
+        assert_eq!(dr.created(), 10 + 2);
+    }
+
+    #[test]
+    fn map_unchecked_panic() {
+        let dr = DropCounter::new();
+
+        let vec = (0..10).map(|x| dr.create(x)).collect::<Vec<_>>();
+
+        let mapped = panics_at(&dr, 5, |x| x + 10, |f| unsafe { vec.map_unchecked(f) });
+        assert_eq!(mapped, 5);
+    }
+
+    #[test]
+    fn map_collect_errors() {
+        let dr = DropCounter::new();
+
+        let vec = (0..10).map(|x| dr.create(x)).collect::<Vec<_>>();
+
+        let res = vec.map_collect_errors(|x| {
+            if *x.get() % 3 == 1 {
+                Err(dr.create(*x.get()))
+            } else {
+                Ok(dr.create(*x.get() + 10))
+            }
+        });
+
+        let errors = res.err().unwrap();
+        assert_eq!(
+            errors.iter().map(|x| *x.get()).collect::<Vec<_>>(),
+            [1, 4, 7]
+        );
+    }
+
+    #[test]
+    fn try_map_empty() {
+        let vec = Vec::<drop_counter::OnDrop<'_, i32>>::with_capacity(10);
+
+        let res = vec.try_map(|_| -> Result<drop_counter::OnDrop<'_, i32>, ()> { unreachable!() });
+        assert!(res.unwrap().is_empty());
+    }
+
+    #[test]
+    fn try_map_first_error() {
+        let dr = DropCounter::new();
+
+        let vec = vec![dr.create(0)];
+        assert!(vec
+            .try_map(|_| Err::<drop_counter::OnDrop<'_, i32>, _>(()))
+            .is_err());
+
+        let vec = (0..10).map(|x| dr.create(x)).collect::<Vec<_>>();
+        assert!(vec
+            .try_map(|_| Err::<drop_counter::OnDrop<'_, i32>, _>(()))
+            .is_err());
+    }
+
+    #[test]
+    fn try_zip_with_empty() {
+        let dr = DropCounter::new();
+
+        let a = Vec::<drop_counter::OnDrop<'_, i32>>::with_capacity(10);
+        let b = (0..10).map(|x| dr.create(x)).collect::<Vec<_>>();
+
+        let res = a.try_zip_with(b, |_, _| -> Result<drop_counter::OnDrop<'_, i32>, ()> {
+            unreachable!()
+        });
+        assert!(res.unwrap().is_empty());
+    }
+
+    #[test]
+    fn try_zip_with_first_error() {
+        let dr = DropCounter::new();
+
+        let a = vec![dr.create(0)];
+        let b = (0..10).map(|x| dr.create(x)).collect::<Vec<_>>();
+        assert!(a
+            .try_zip_with(b, |_, _| Err::<drop_counter::OnDrop<'_, i32>, _>(()))
+            .is_err());
+
+        let a = (0..10).map(|x| dr.create(x)).collect::<Vec<_>>();
+        let b = (0..10).map(|x| dr.create(x)).collect::<Vec<_>>();
+        assert!(
+            try_zip_with!((a, b), |_x, _y| Err::<drop_counter::OnDrop<'_, i32>, _>(())).is_err()
+        );
+    }
+
+    #[test]
+    fn map_split2_panic() {
+        let dr = DropCounter::new();
+
+        let vec = (0..10).map(|x| dr.create(x)).collect::<Vec<_>>();
+
+        assert_panics(|| {
+            vec.map_split2(|x| {
+                stop_at(&x, 5);
+
+                (dr.create(*x.get() as u8), dr.create(*x.get() as f32))
+            })
+        });
+
+        // a pair of outputs for each of 0..5
+        assert_eq!(dr.created(), 10 + 2 * 5);
+    }
+
+    #[test]
+    fn replace_map() {
+        let dr = DropCounter::new();
+
+        let mut vec = (0..10).map(|x| dr.create(x)).collect::<Vec<_>>();
+
+        let mapped = vec.replace_map(|x| dr.create(*x.get() as f32));
+        assert_eq!(mapped.len(), 10);
+        drop(mapped);
+
+        vec.extend((0..10).map(|x| dr.create(x)));
+
+        let mapped = panics_at(&dr, 5, |x| x as f32, |f| vec.replace_map(f));
+        assert_eq!(mapped, 5);
+        assert!(vec.is_empty());
+    }
+
+    #[test]
+    fn zip_with_until() {
+        let dr = DropCounter::new();
+
+        let a = (0..10).map(|x| dr.create(x)).collect::<Vec<_>>();
+        let b = (0..8).map(|x| dr.create(x)).collect::<Vec<_>>();
+
+        let vec = a.zip_with_until(b, |x, y| {
+            let value = *x.get() + *y.get();
+            (dr.create(value), value == 6)
+        });
+        assert_eq!(vec.len(), 4);
+
+        let a = (0..10).map(|x| dr.create(x)).collect::<Vec<_>>();
+        let b = (0..8).map(|x| dr.create(x as f32)).collect::<Vec<_>>();
+        let created = dr.created();
+
+        assert_panics(|| {
+            a.zip_with_until(b, |x, y| {
+                stop_at(&x, 5);
+
+                (dr.create(*y.get()), false)
+            })
+        });
+
+        assert_eq!(dr.created() - created, 5);
+    }
+
+    #[test]
+    fn group_map() {
+        let dr = DropCounter::new();
+
+        let vec = (0..10).map(|x| dr.create(x)).collect::<Vec<_>>();
+        let vec = vec.group_map(|x| *x.get() / 3, |run| dr.create(run.len()));
+        assert_eq!(vec.len(), 4);
+
+        let vec = (0..10).map(|x| dr.create(x)).collect::<Vec<_>>();
+        let created = dr.created();
+
+        assert_panics(|| {
+            vec.group_map(
+                |x| *x.get() / 3,
+                |run| {
+                    stop_at(&run[0], 6);
+
+                    dr.create(run.len())
+                },
+            )
+        });
+
+        // only the runs starting at 0 and 3 were mapped
+        assert_eq!(dr.created() - created, 2);
+    }
+
+    #[test]
+    fn map_with_progress_panic() {
+        let dr = DropCounter::new();
+        let mut reports = Vec::new();
+
+        let vec = (0..10).map(|x| dr.create(x)).collect::<Vec<_>>();
+
+        let mapped = panics_at(
+            &dr,
+            6,
+            |x| x,
+            |f| vec.map_with_progress(4, |len| reports.push(len), f),
+        );
+
+        // progress is only reported for whole blocks
+        assert_eq!(mapped, 6);
+        assert_eq!(reports, [4]);
+    }
+
+    #[test]
+    fn try_map_flatten() {
+        let dr = DropCounter::new();
+
+        let vec = (0..10).map(|x| dr.create(x)).collect::<Vec<_>>();
+        let outer = vec.try_map_flatten(|x| match *x.get() {
+            4 => Err(()),
+            x => Ok(Ok(dr.create(x))),
+        });
+        assert!(outer.is_err());
+
+        let vec = (0..10).map(|x| dr.create(x)).collect::<Vec<_>>();
+        let inner = vec.try_map_flatten(|x| match *x.get() {
+            7 => Ok(Err(())),
+            x => Ok(Ok(dr.create(x))),
+        });
+        assert!(inner.is_err());
+    }
+
+    #[test]
+    fn map_track_count() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let dr = DropCounter::new();
+        let counter = AtomicUsize::new(0);
+
+        let vec = (0..10).map(|x| dr.create(x)).collect::<Vec<_>>();
+
+        let mapped = panics_at(&dr, 6, |x| x, |f| vec.map_track_count(&counter, f));
+        assert_eq!(mapped, 6);
+        assert_eq!(counter.load(Ordering::Relaxed), 6);
+    }
+
+    #[test]
+    fn zip_into_map() {
+        let dr = DropCounter::new();
+
+        let keys = vec![0, 1, 0, 2];
+        let values = (0..10).map(|x| dr.create(x)).collect::<Vec<_>>();
+
+        let map = keys.zip_into_map(values);
+        assert_eq!(map.len(), 3);
+    }
+
+    #[test]
+    fn zip_into_map_with_panic() {
+        let dr = DropCounter::new();
+
+        let keys = vec![0, 1, 2, 0, 3, 4];
+        let values = (0..10).map(|x| dr.create(x)).collect::<Vec<_>>();
+        let mut combined = None;
+
+        assert_panics(|| {
+            keys.zip_into_map_with(values, |old, new| {
+                combined = Some((*old.get(), *new.get()));
+                panic!("stop")
+            })
+        });
+
+        // the first repeated key is `0`, at indices 0 and 3
+        assert_eq!(combined, Some((0, 3)));
+    }
+
+    #[test]
+    fn clear_and_reuse() {
+        let dr = DropCounter::new();
+
+        let vec = (0..10).map(|x| dr.create(x)).collect::<Vec<_>>();
+        let mut vec = vec.clear_and_reuse();
+        vec.push(dr.create(1.0f32));
+
+        let vec = (0..10).map(|x| dr.create(x)).collect::<Vec<_>>();
+        let mut vec = vec.clear_and_reuse::<drop_counter::OnDrop<'_, [u8; 3]>>();
+        vec.push(dr.create([0; 3]));
+    }
+
+    #[test]
+    fn flatten_options() {
+        let dr = DropCounter::new();
+
+        let vec = (0..10)
+            .map(|x| if x % 3 == 0 { None } else { Some(dr.create(x)) })
+            .collect::<Vec<_>>();
+
+        let vec = vec_utils::flatten_options(vec);
+        assert_eq!(vec.len(), 6);
+    }
+
+    #[test]
+    fn take_map_panic() {
+        let dr = DropCounter::new();
+
+        let mut vec = (0..10).map(|x| dr.create(x)).collect::<Vec<_>>();
+
+        let mapped = panics_at(&dr, 4, |x| x, |f| vec.take_map(f));
+
+        // the vector was taken before mapping, so it's left empty
+        assert_eq!(mapped, 4);
+        assert_eq!(vec.capacity(), 0);
+    }
+
+    #[test]
+    fn zip_with_smaller() {
+        let dr = DropCounter::new();
+
+        let a = (0..9).map(|x| dr.create([x as u64; 4])).collect::<Vec<_>>();
+        let b = (0..6).map(|x| dr.create(x)).collect::<Vec<_>>();
+
+        // the output is smaller, and the leftover elements of `a` are dropped
+        // before the allocation is shrunk to fit a whole number of outputs
+        let vec = a.zip_with(b, |x, y| dr.create(x.get()[0] + *y.get() as u64));
+        assert_eq!(vec.len(), 6);
+
+        let a = (0..10)
+            .map(|x| dr.create([x as u64; 4]))
+            .collect::<Vec<_>>();
+        let b = (0..6).map(|x| dr.create(x)).collect::<Vec<_>>();
+        let created = dr.created();
+
+        assert_panics(|| {
+            a.zip_with(b, |x, y| {
+                stop_at(&y, 3);
+
+                dr.create(x.get()[0])
+            })
+        });
+
+        assert_eq!(dr.created() - created, 3);
+    }
+
+    #[test]
+    fn zip_with_larger() {
+        let dr = DropCounter::new();
+
+        let mut a = Vec::with_capacity(20);
+        a.extend((0..8).map(|x| dr.create(x)));
+        let b = (0..6).map(|x| dr.create(x)).collect::<Vec<_>>();
+
+        let vec = a.zip_with(b, |x, y| (x, y));
+        assert_eq!(vec.len(), 6);
+
+        let mut a = Vec::with_capacity(20);
+        a.extend((0..8).map(|x| dr.create(x)));
+        let b = (0..6).map(|x| dr.create(x)).collect::<Vec<_>>();
+        let mut zipped = 0;
+
+        assert_panics(|| {
+            a.zip_with(b, |x, y| {
+                stop_at(&y, 3);
+                zipped += 1;
+
+                (x, y)
+            })
+        });
+
+        assert_eq!(zipped, 3);
+    }
+
+    #[test]
+    fn zip_with_grow() {
+        let dr = DropCounter::new();
+
+        // neither input has any spare capacity for the pairs
+        let a = (0..8).map(|x| dr.create(x)).collect::<Vec<_>>();
+        let b = (0..6).map(|x| dr.create(x)).collect::<Vec<_>>();
+
+        let vec = a.zip_with(b, |x, y| (x, y));
+        assert_eq!(vec.len(), 6);
+
+        let a = (0..8).map(|x| dr.create(x)).collect::<Vec<_>>();
+        let b = (0..6).map(|x| dr.create(x)).collect::<Vec<_>>();
+        let mut zipped = 0;
+
+        assert_panics(|| {
+            a.zip_with(b, |x, y| {
+                stop_at(&y, 3);
+                zipped += 1;
+
+                (x, y)
+            })
+        });
+
+        assert_eq!(zipped, 3);
+    }
+
+    #[test]
+    fn map_send() {
+        use std::sync::mpsc::sync_channel;
+
+        let dr = DropCounter::new();
+
+        let vec = (0..10).map(|x| dr.create(x)).collect::<Vec<_>>();
+        let (tx, rx) = sync_channel(10);
+
+        assert!(vec.map_send(tx, |x| x).is_ok());
+        assert_eq!(rx.iter().count(), 10);
+
+        // the receiver is dropped once it got 3 values
+        let vec = (0..10).map(|x| dr.create(x)).collect::<Vec<_>>();
+        let (tx, rx) = sync_channel(0);
+
+        std::thread::scope(|s| {
+            s.spawn(move || rx.iter().take(3).for_each(drop));
+
+            assert!(vec.map_send(tx, |x| x).is_err());
+        });
+    }
+
+    #[test]
+    fn map_send_panic() {
+        use std::sync::mpsc::sync_channel;
+
+        let dr = DropCounter::new();
+
+        let vec = (0..10).map(|x| dr.create(x)).collect::<Vec<_>>();
+        let (tx, rx) = sync_channel(10);
+
+        let mapped = panics_at(&dr, 4, |x| x, |f| vec.map_send(tx, f));
+
+        // every value that was mapped was sent before the panic
+        assert_eq!(mapped, 4);
+        assert_eq!(rx.iter().count(), 4);
+    }
+
+    #[test]
+    fn wrap() {
+        use std::cmp::Reverse;
+
+        let dr = DropCounter::new();
+
+        let vec = (0..10).map(|x| dr.create(x)).collect::<Vec<_>>();
+        let mut vec = vec.wrap::<Reverse<_>>();
+        vec.sort_by_key(|x| Reverse(*x.0.get()));
+
+        let vec = vec.unwrap_newtype();
+        assert_eq!(*vec[0].get(), 9);
+    }
+
+    #[test]
+    fn map_expanding() {
+        let dr = DropCounter::new();
+
+        let vec = (0..10).map(|x| dr.create(x % 3)).collect::<Vec<_>>();
+        let vec = vec.map_expanding(|x, out| out.extend((0..*x.get()).map(|y| dr.create(y))));
+        assert_eq!(vec.len(), 9);
+
+        // the output catches up to the unread input
+        let vec = (0..10).map(|x| dr.create(3 - x % 4)).collect::<Vec<_>>();
+        let vec = vec.map_expanding(|x, out| out.extend((0..*x.get()).map(|y| dr.create(y))));
+        assert_eq!(vec.len(), 17);
+    }
+
+    #[test]
+    fn map_expanding_panic() {
+        let dr = DropCounter::new();
+
+        let vec = (0..10).map(|x| dr.create(x % 3)).collect::<Vec<_>>();
+        let created = dr.created();
+        let mut count = 0;
+
+        assert_panics(|| {
+            vec.map_expanding(|x, out| {
+                count += 1;
+                out.push(dr.create(*x.get()));
+
+                if count == 6 {
+                    panic!("stop");
+                }
+            })
+        });
+
+        // the value pushed by the call that panicked is dropped too
+        assert_eq!(dr.created() - created, 6);
+
+        // the panic happens after the output caught up to the unread input
+        let vec = (0..10).map(|x| dr.create(3 - x % 4)).collect::<Vec<_>>();
+        let created = dr.created();
+        let mut count = 0;
+
+        assert_panics(|| {
+            vec.map_expanding(|x, out| {
+                count += 1;
+                out.extend((0..*x.get()).map(|y| dr.create(y)));
+
+                if count == 6 {
+                    panic!("stop");
+                }
+            })
+        });
+
+        // 3, 2, 1, 0, 3, and 2 values were pushed
+        assert_eq!(dr.created() - created, 11);
+    }
+
+    #[test]
+    fn map_two_phase() {
+        let dr = DropCounter::new();
+
+        let vec = (0..10).map(|x| dr.create(3 - x % 4)).collect::<Vec<_>>();
+        let vec = vec.map_two_phase(
+            |x| *x.get() as usize,
+            |x, out| out.extend((0..*x.get()).map(|y| dr.create(y))),
+        );
+        assert_eq!(vec.len(), 17);
+    }
+
+    #[test]
+    fn map_two_phase_panic() {
+        let dr = DropCounter::new();
+
+        let vec = (0..10).map(|x| dr.create(3 - x % 4)).collect::<Vec<_>>();
+        let mut count = 0;
+
+        assert_panics(|| {
+            vec.map_two_phase(
+                |x| *x.get() as usize,
+                |x, out| {
+                    count += 1;
+                    out.extend((0..*x.get()).map(|y| dr.create(y)));
+
+                    if count == 6 {
+                        panic!("stop");
+                    }
+                },
+            )
+        });
+
+        // 3, 2, 1, 0, 3, and 2 values were filled
+        assert_eq!(dr.created(), 10 + 11);
+    }
+
+    #[test]
+    fn drop_and_reuse() {
+        let dr = DropCounter::new();
+
+        let vec = (0..10).map(|x| dr.create(x)).collect::<Vec<_>>();
+
+        let mut vec = vec.drop_and_reuse::<[usize; 3]>();
+        vec.extend((0..vec.capacity()).map(|x| [x; 3]));
+
+        let vec = vec.drop_and_reuse::<usize>();
+        assert!(vec.capacity() > 0);
+    }
+
+    #[test]
+    fn map_into_panic() {
+        let dr = DropCounter::new();
+
+        let vec = (0..10).map(|x| dr.create(x)).collect::<Vec<_>>();
+        let mut out = vec![dr.create(-1)];
+
+        let mapped = panics_at(&dr, 4, |x| x, |f| vec.map_into(&mut out, f));
+
+        // the values that were pushed before the panic are kept
+        assert_eq!(mapped, 4);
+        assert_eq!(
+            out.iter().map(|x| *x.get()).collect::<Vec<_>>(),
+            [-1, 0, 1, 2, 3]
+        );
+    }
+
+    #[test]
+    fn map_indexed_panic() {
+        let dr = DropCounter::new();
+
+        let vec = (0..10).map(|x| dr.create(x)).collect::<Vec<_>>();
+
+        assert_panics(|| {
+            vec.map_indexed(|i, x| {
+                assert_eq!(i, *x.get());
+                stop_at(&x, 4);
+
+                dr.create(i)
+            })
+        });
+
+        assert_eq!(dr.created(), 10 + 4);
+    }
+
+    #[test]
+    fn try_map_indexed() {
+        let dr = DropCounter::new();
+
+        let vec = (0..10).map(|x| dr.create(x)).collect::<Vec<_>>();
+
+        let err = vec
+            .try_map_indexed(|i, x| {
+                assert_eq!(i, *x.get());
+
+                if i == 6 {
+                    Err(i)
+                } else {
+                    Ok(dr.create(i as f32))
+                }
+            })
+            .is_err();
+
+        assert!(err);
+    }
+
+    // a zero-sized type that tracks how many of it are alive on this thread
+    struct Zst;
+
+    thread_local! {
+        static LIVE_ZST: std::cell::Cell<isize> = const { std::cell::Cell::new(0) };
+    }
+
+    impl Zst {
+        fn new() -> Self {
+            LIVE_ZST.with(|live| live.set(live.get() + 1));
+            Zst
+        }
+
+        fn live() -> isize {
+            LIVE_ZST.with(|live| live.get())
+        }
+    }
+
+    impl Drop for Zst {
+        fn drop(&mut self) {
+            LIVE_ZST.with(|live| live.set(live.get() - 1));
+        }
+    }
+
+    #[test]
+    fn map_zst() {
+        let vec = (0..10).map(|_| Zst::new()).collect::<Vec<_>>();
+        let vec = vec.map(|_| Zst::new());
+        assert_eq!(vec.len(), 10);
+        assert_eq!(Zst::live(), 10);
+
+        assert_panics(|| {
+            vec.map_indexed(|i, _| {
+                if i == 5 {
+                    panic!("stop");
+                }
+
+                Zst::new()
+            })
+        });
+
+        assert_eq!(Zst::live(), 0);
+    }
+
+    #[test]
+    fn zip_with_zst() {
+        let a = (0..10).map(|_| Zst::new()).collect::<Vec<_>>();
+        let b = (0..4).map(|_| Zst::new()).collect::<Vec<_>>();
+
+        let vec = a.zip_with(b, |_, _| Zst::new());
+        assert_eq!(vec.len(), 4);
+        assert_eq!(Zst::live(), 4);
+
+        drop(vec);
+        assert_eq!(Zst::live(), 0);
+    }
+
+    #[test]
+    fn map_empty() {
+        let dr = DropCounter::new();
+
+        let vec = Vec::<drop_counter::OnDrop<'_, i32>>::new();
+        let vec = vec.map(|x| dr.create(*x.get() as f32));
+        assert!(vec.is_empty());
+
+        let vec = vec![dr.create(0)];
+        let res = vec.try_map(|_| Err::<drop_counter::OnDrop<'_, f32>, _>(()));
+        assert!(res.is_err());
+
+        let vec = vec![dr.create(0)];
+        let res = vec.try_zip_with(Vec::<drop_counter::OnDrop<'_, i32>>::new(), |x, _| {
+            Err::<drop_counter::OnDrop<'_, i32>, _>(x)
+        });
+        assert!(res.ok().is_some_and(|vec| vec.is_empty()));
+    }
+
+    #[test]
+    fn into_map_iter() {
+        let dr = DropCounter::new();
+
+        let vec = (0..10).map(|x| dr.create(x)).collect::<Vec<_>>();
+        let mut iter = vec.into_map_iter(|x| dr.create(*x.get() as f32));
+
+        iter.next();
+        iter.next();
+        drop(iter);
+
+        let vec = (0..10).map(|x| dr.create(x)).collect::<Vec<_>>();
+        let mut iter = vec.into_map_iter(|x| dr.create(*x.get() as f32));
+
+        iter.next();
+        let vec = iter.into_vec();
+        assert_eq!(vec.len(), 9);
+    }
+
+    #[test]
+    fn into_map_iter_double_ended() {
+        let dr = DropCounter::new();
+
+        let vec = (0..10).map(|x| dr.create(x)).collect::<Vec<_>>();
+        let mut iter = vec.into_map_iter(|x| dr.create(*x.get() as f32));
+
+        iter.next_back();
+        iter.next();
+        iter.next_back();
+        drop(iter);
+
+        let vec = (0..10).map(|x| dr.create(x)).collect::<Vec<_>>();
+        let mut iter = vec.into_map_iter(|x| dr.create(*x.get() as f32));
+
+        iter.next_back();
+        iter.next();
+        iter.next_back();
+        let vec = iter.into_vec();
+        assert_eq!(vec.len(), 7);
+        assert_eq!(*vec[0].get(), 1.0);
+        assert_eq!(*vec[6].get(), 7.0);
+    }
+
+    #[test]
+    fn into_map_iter_back_panic() {
+        let dr = DropCounter::new();
+
+        let vec = (0..10).map(|x| dr.create(x)).collect::<Vec<_>>();
+        let mut iter = vec.into_map_iter(|x| {
+            stop_at(&x, 7);
+
+            dr.create(*x.get() as f32)
+        });
+
+        iter.next();
+        iter.next_back();
+        iter.next_back();
+
+        // 0, 9, and 8 were mapped before reaching 7 from the back
+        assert_panics(|| iter.next_back());
+        assert_eq!(dr.created(), 10 + 3);
+        drop(iter);
+    }
+
+    #[test]
+    fn into_map_iter_panic() {
+        let dr = DropCounter::new();
+
+        let vec = (0..10).map(|x| dr.create(x)).collect::<Vec<_>>();
+        let mut iter = vec.into_map_iter(|x| {
+            stop_at(&x, 5);
+
+            dr.create(*x.get() as f32)
+        });
+
+        iter.next();
+
+        // `into_vec` maps 1 through 4 before the panic
+        assert_panics(|| iter.into_vec());
+        assert_eq!(dr.created(), 10 + 5);
+    }
+
+    #[test]
+    fn chunks_map() {
+        let dr = DropCounter::new();
+
+        let vec = (0..10).map(|x| dr.create(x)).collect::<Vec<_>>();
+        let (vec, rem) = vec.chunks_map(|[a, b, c]: [_; 3]| {
+            dr.create(*a.get() as f32 + *b.get() as f32 + *c.get() as f32)
+        });
+
+        assert_eq!(vec.len(), 3);
+        assert_eq!(rem.len(), 1);
+    }
+
+    #[test]
+    fn chunks_map_panic() {
+        let dr = DropCounter::new();
+
+        let vec = (0..10).map(|x| dr.create(x)).collect::<Vec<_>>();
+
+        assert_panics(|| {
+            vec.chunks_map(|[a, _]: [_; 2]| {
+                stop_at(&a, 4);
+
+                dr.create(*a.get() as u8)
+            })
+        });
+
+        // only the chunks starting at 0 and 2 were mapped
+        assert_eq!(dr.created(), 10 + 2);
+    }
+
+    #[test]
+    fn windows_map() {
+        let dr = DropCounter::new();
+
+        let vec = (0..10).map(|x| dr.create(x)).collect::<Vec<_>>();
+        let vec = vec.windows_map(3, |w| dr.create(*w[0].get() as f32 + *w[2].get() as f32));
+
+        assert_eq!(vec.len(), 8);
+    }
+
+    #[test]
+    fn windows_map_panic() {
+        let dr = DropCounter::new();
+
+        let vec = (0..10).map(|x| dr.create(x)).collect::<Vec<_>>();
+
+        assert_panics(|| {
+            vec.windows_map(4, |w| {
+                stop_at(&w[0], 3);
+
+                dr.create(*w[1].get() as u8)
+            })
+        });
+
+        // only the windows starting at 0, 1, and 2 were mapped
+        assert_eq!(dr.created(), 10 + 3);
+    }
+
+    #[test]
+    fn scan_map_panic() {
+        let dr = DropCounter::new();
+
+        let vec = (0..10).map(|x| dr.create(x)).collect::<Vec<_>>();
+        let created = dr.created();
+
+        assert_panics(|| {
+            vec.scan_map(dr.create(0), |sum, x| {
+                stop_at(&x, 6);
+
+                *sum = dr.create(*sum.get() + *x.get());
+                dr.create(*sum.get() as f32)
+            })
+        });
+
+        // the initial state, then a new state and an output for each of 0..6
+        assert_eq!(dr.created() - created, 1 + 2 * 6);
+    }
+
+    #[test]
+    fn retain_map_panic() {
+        let dr = DropCounter::new();
+
+        let vec = (0..10).map(|x| dr.create(x)).collect::<Vec<_>>();
+
+        assert_panics(|| {
+            vec.retain_map(|x| {
+                stop_at(&x, 7);
+
+                let x = *x.get();
+
+                if x % 3 == 0 {
+                    None
+                } else {
+                    Some(dr.create(x * 2))
+                }
+            })
+        });
+
+        // only 1, 2, 4, and 5 were kept before the panic
+        assert_eq!(dr.created(), 10 + 4);
+    }
+
+    #[test]
+    fn dedup_map() {
+        let dr = DropCounter::new();
+
+        let vec = (0..10).map(|x| dr.create(x)).collect::<Vec<_>>();
+        let vec = vec.dedup_map(|x| dr.create(*x.get() / 4), |x| *x.get());
+
+        assert_eq!(vec.len(), 3);
+    }
+
+    #[test]
+    fn map_copy_panic() {
+        let vec = (0..10u32).collect::<Vec<_>>();
+        let mut mapped = 0;
+
+        assert_panics(|| {
+            vec.map(|x| {
+                if x == 5 {
+                    panic!("stop");
+                }
+
+                mapped += 1;
+                x as f32
+            })
+        });
+
+        assert_eq!(mapped, 5);
+
+        let vec = (0..10u32).collect::<Vec<_>>();
+        let res = vec.try_map(|x| if x == 5 { None } else { Some(x as u64) });
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn map_catch_unwind() {
+        let dr = DropCounter::new();
+
+        let vec = (0..10).map(|x| dr.create(x)).collect::<Vec<_>>();
+
+        let res = vec.map_catch_unwind(|x| {
+            if *x.get() == 6 {
+                panic!("stop");
+            }
+
+            dr.create(*x.get() as f32)
+        });
+
+        assert_eq!(res.err().map(|(vec, _)| vec.len()), Some(6));
+    }
+
+    #[test]
+    fn try_map_resumable() {
+        let dr = DropCounter::new();
+
+        for (out_len, rest_len) in [(0, 9), (4, 5), (9, 0)] {
+            let vec = (0..10).map(|x| dr.create(x)).collect::<Vec<_>>();
+
+            let res = vec.try_map_resumable(|x| {
+                if *x.get() == out_len {
+                    Err(x)
+                } else {
+                    Ok(dr.create(*x.get() as f32))
+                }
+            });
+
+            let (out, _, rest) = match res {
+                Err(res) => res,
+                Ok(_) => panic!("expected an error"),
+            };
+
+            assert_eq!(out.len(), out_len as usize);
+            assert_eq!(rest.len(), rest_len);
+        }
+
+        let vec = (0..10).map(|x| dr.create(x as u8)).collect::<Vec<_>>();
+        let res = vec.try_map_resumable(|x| {
+            if *x.get() == 3 {
+                None
+            } else {
+                Some(dr.create([*x.get() as u64; 4]))
+            }
+        });
+
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn with_scratch() {
+        let dr = DropCounter::new();
+
+        let mut vec: Vec<drop_counter::OnDrop<'_, u32>> = Vec::with_capacity(8);
+        vec.push(dr.create(0));
+
+        vec.with_scratch(|scratch| {
+            scratch.push(dr.create(1));
+            scratch.push(dr.create(2));
+            drop(scratch.pop());
+            scratch.push(dr.create(3));
+        });
+
+        assert_eq!(vec.len(), 1);
+    }
+
+    #[test]
+    fn with_scratch_panic() {
+        let dr = DropCounter::new();
+
+        let mut vec: Vec<drop_counter::OnDrop<'_, u32>> = Vec::with_capacity(8);
+        vec.push(dr.create(0));
+        let spare = vec.capacity() - vec.len();
+
+        assert_panics(|| {
+            vec.with_scratch(|scratch| {
+                for i in 1.. {
+                    scratch.push(dr.create(i));
+                }
+            })
+        });
+
+        // the scratch buffer was filled, then the push after that panicked
+        assert_eq!(dr.created(), 1 + spare + 1);
+        assert_eq!(vec.len(), 1);
+    }
+
+    #[test]
+    fn zip_with_same() {
+        let dr = DropCounter::new();
+
+        let mut a = (0i32..10).map(|x| dr.create(x)).collect::<Vec<_>>();
+        let b = (20i32..30).map(|x| dr.create(x)).collect::<Vec<_>>();
+
+        let mut flip = false;
+
+        a = a.zip_with(b, |x, y| {
+            flip = !flip;
+
+            if flip {
+                x
+            } else {
+                y
+            }
+        });
+    }
+
+    #[test]
+    fn zip_with_diff() {
+        let dr = DropCounter::new();
+
+        let a = (0..10).map(|x| dr.create(x)).collect::<Vec<_>>();
+        let mut b = (20..40).map(|x| dr.create(x)).collect::<Vec<_>>();
+
+        let mut flip = false;
+
+        b = a.zip_with(b, |x, y| {
+            flip = !flip;
+
+            if flip {
+                x
+            } else {
+                y
+            }
+        });
+    }
+
+    #[test]
+    fn try_zip_with() {
+        let dr = DropCounter::new();
+
+        let a = (0..10).map(|x| dr.create(x)).collect::<Vec<_>>();
+        let b = (20..40).map(|x| dr.create(x)).collect::<Vec<_>>();
+
+        let mut flip = false;
+        let mut counter = 0;
+
+        let err = a
+            .try_zip_with(b, |x, y| {
+                flip = !flip;
+                counter += 1;
+
+                if counter == 5 {
+                    None
+                } else {
+                    Some(dr.create((*x.get()) as f32 + *y.get() as f32))
+                }
+            })
+            .is_err();
+
+        assert!(err);
+    }
+
+    #[test]
+    fn map_in_place_panic() {
+        let dr = DropCounter::new();
+
+        let mut vec = (0..10).map(|x| dr.create(x)).collect::<Vec<_>>();
+
+        assert_panics(|| {
+            vec.map_in_place(|x| {
+                stop_at(x, 5);
+
+                *x = dr.create(x.get() * 10);
+            })
+        });
+
+        assert_eq!(
+            vec.iter().map(|x| *x.get()).collect::<Vec<_>>(),
+            [0, 10, 20, 30, 40, 5, 6, 7, 8, 9]
+        );
+    }
+
+    #[test]
+    fn zip3_with() {
+        let dr = DropCounter::new();
+
+        let a = (0..10).map(|x| dr.create(x)).collect::<Vec<_>>();
+        let b = (20..25).map(|x| dr.create(x)).collect::<Vec<_>>();
+        let c = (40..60).map(|x| dr.create(x)).collect::<Vec<_>>();
+
+        a.zip3_with(b, c, |x, y, z| dr.create(x.get() + y.get() + z.get()));
+    }
+
+    #[test]
+    fn try_zip3_with() {
+        let dr = DropCounter::new();
+
+        let a = (0..10).map(|x| dr.create(x)).collect::<Vec<_>>();
+        let b = (20..25).map(|x| dr.create(x)).collect::<Vec<_>>();
+        let c = (40..60).map(|x| dr.create(x as f32)).collect::<Vec<_>>();
+
+        let mut counter = 0;
+
+        let err = a
+            .try_zip3_with(b, c, |x, y, z| {
+                counter += 1;
+
+                if counter == 3 {
+                    None
+                } else {
+                    Some(dr.create((x.get() + y.get()) as f32 + z.get()))
+                }
+            })
+            .is_err();
+
+        assert!(err);
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn par_map() {
+        let dr = DropCounter::new();
+
+        let vec = (0..1000).map(|x| dr.create(x)).collect::<Vec<_>>();
+
+        vec.par_map(|x| dr.create(x.get() * 2));
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn par_map_panic() {
+        let dr = DropCounter::new();
+
+        let vec = (0..1000).map(|x| dr.create(x)).collect::<Vec<_>>();
+
+        assert_panics(|| {
+            vec.par_map(|x| {
+                if x.get() % 300 == 299 {
+                    panic!("stop");
+                }
+
+                dr.create(x.get() * 2)
+            })
+        });
+
+        // the order of the chunks isn't known, but 299, 599, and 899 are never mapped
+        assert!(dr.created() <= 1000 + 997);
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn par_map_in_pool_panic() {
+        let dr = DropCounter::new();
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(2)
+            .build()
+            .unwrap();
+
+        let vec = (0..1000).map(|x| dr.create(x)).collect::<Vec<_>>();
+
+        assert_panics(|| {
+            vec.par_map_in_pool(&pool, |x| {
+                if x.get() % 300 == 299 {
+                    panic!("stop");
+                }
+
+                dr.create(x.get() * 2)
+            })
+        });
+
+        // the order of the chunks isn't known, but 299, 599, and 899 are never mapped
+        assert!(dr.created() <= 1000 + 997);
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn par_zip_with() {
+        let dr = DropCounter::new();
+
+        let a = (0..1000).map(|x| dr.create(x)).collect::<Vec<_>>();
+        let b = (0..700).map(|x| dr.create(x as f32)).collect::<Vec<_>>();
+
+        let vec = a.par_zip_with(b, |x, y| dr.create(*x.get() as f32 + y.get()));
+        assert_eq!(vec.len(), 700);
+
+        let a = (0..300).map(|x| dr.create(x as f32)).collect::<Vec<_>>();
+        let b = (0..1000).map(|x| dr.create(x)).collect::<Vec<_>>();
+
+        let vec = a.par_zip_with(b, |x, y| dr.create(*x.get() as i32 + y.get()));
+        assert_eq!(vec.len(), 300);
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn par_zip_with_panic() {
+        let dr = DropCounter::new();
+
+        let a = (0..1000).map(|x| dr.create(x)).collect::<Vec<_>>();
+        let b = (0..900).map(|x| dr.create(x)).collect::<Vec<_>>();
+
+        assert_panics(|| {
+            a.par_zip_with(b, |x, y| {
+                if x.get() % 300 == 299 {
+                    panic!("stop");
+                }
+
+                dr.create(x.get() + y.get())
+            })
+        });
+
+        // only 900 pairs are zipped, and 299, 599, and 899 are never mapped
+        assert!(dr.created() <= 1900 + 897);
+    }
+}
+
+mod tuple {
+    #![allow(unused_assignments)]
+    use super::*;
+    use crate::{try_zip_with, zip_with};
+
+    #[test]
+    fn map() {
+        let dr = DropCounter::new();
+
+        let vec = (0..10).map(|x| dr.create(x)).collect::<Vec<_>>();
+
+        zip_with!(vec, |x| dr.create(*x.get()));
+    }
+
+    #[test]
+    fn try_map() {
+        let dr = DropCounter::new();
+
+        let vec = (0..10).map(|x| dr.create(x)).collect::<Vec<_>>();
+
+        let mut counter = 0;
+
+        let err = try_zip_with!(vec, |x| {
+            counter += 1;
+
+            if counter == 3 {
+                None
+            } else {
+                Some(dr.create(*x.get() as f32))
             }
         });
 
         assert!(err.is_err());
     }
 
+    #[test]
+    fn vec_zip() {
+        let dr = DropCounter::new();
+
+        let a = (0..10).map(|x| dr.create(x)).collect::<Vec<_>>();
+        let b = (10..14).map(|x| dr.create(x)).collect::<Vec<_>>();
+        let c = (20..27).map(|x| dr.create(x)).collect::<Vec<_>>();
+        let d = (30..40).map(|x| dr.create(x as f32)).collect::<Vec<_>>();
+
+        // the leftover elements of `a`, `c`, and `d` are dropped
+        let vec = (a, b, c, d)
+            .zip_with(|(a, b, c, d)| dr.create(*a.get() + *b.get() + *c.get() + *d.get() as i32));
+
+        assert_eq!(vec.len(), 4);
+    }
+
+    #[test]
+    fn vec_zip_panic() {
+        let dr = DropCounter::new();
+
+        let a = (0..10).map(|x| dr.create(x)).collect::<Vec<_>>();
+        let b = (10..14).map(|x| dr.create(x)).collect::<Vec<_>>();
+        let c = (20..27).map(|x| dr.create(x)).collect::<Vec<_>>();
+        let d = (30..40).map(|x| dr.create(x as f32)).collect::<Vec<_>>();
+        let created = dr.created();
+
+        assert_panics(|| {
+            (a, b, c, d).zip_with(|(a, b, c, d)| {
+                stop_at(&a, 2);
+
+                dr.create(*a.get() + *b.get() + *c.get() + *d.get() as i32)
+            })
+        });
+
+        assert_eq!(dr.created() - created, 2);
+    }
+
     #[test]
     fn zip_with_same() {
         let dr = DropCounter::new();
@@ -431,7 +2270,7 @@ mod tuple {
 
         unsafe {
             assert!(!DOUBLE_DROP);
-            assert_eq!(DROP_COUNT, 0);
+            assert_eq!({ DROP_COUNT }, 0);
         }
     }
 }
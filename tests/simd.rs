@@ -0,0 +1,60 @@
+#![cfg(feature = "portable_simd")]
+#![feature(portable_simd)]
+
+use std::simd::num::{SimdFloat, SimdUint};
+use std::simd::{f32x8, u32x4, Simd};
+
+use vec_utils::VecExt;
+
+#[macro_use]
+mod common;
+
+#[test]
+fn map_simd() {
+    // not a multiple of the lane count, so the last chunk is padded
+    let vec = (0..21).map(|x| x as f32).collect::<Vec<_>>();
+    let expected = vec.iter().map(|x| x * 2.0).collect::<Vec<_>>();
+    let ptr = vec.as_ptr() as usize;
+
+    let vec = vec.map_simd(|x: f32x8| x * f32x8::splat(2.0));
+    assert_eq!(vec, expected);
+    assert_reuse!(vec.as_ptr() as usize, ptr);
+
+    let vec = vec.map_simd(|x: f32x8| x.to_bits());
+    assert_eq!(
+        vec,
+        expected.iter().map(|x| x.to_bits()).collect::<Vec<_>>()
+    );
+    assert_reuse!(vec.as_ptr() as usize, ptr);
+}
+
+#[test]
+fn map_simd_different_layout() {
+    let vec = vec![1u32, 2, 3, 4, 5, 6];
+
+    let vec = vec.map_simd(|x: u32x4| x.cast::<u8>());
+    assert_eq!(vec, [1, 2, 3, 4, 5, 6]);
+
+    let vec = Vec::<u8>::new().map_simd(|x: Simd<u8, 4>| x);
+    assert!(vec.is_empty());
+}
+
+#[test]
+fn map_simd_panic() {
+    let vec = (0..20).map(|x| x as f32).collect::<Vec<_>>();
+    let mut calls = 0;
+
+    let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        vec.map_simd(|x: f32x8| {
+            calls += 1;
+
+            if calls == 2 {
+                panic!("stop");
+            }
+
+            x
+        })
+    }));
+
+    assert!(res.is_err());
+}
@@ -0,0 +1,75 @@
+#![cfg(feature = "smallvec")]
+
+use smallvec::{smallvec, SmallVec};
+use vec_utils::SmallVecExt;
+
+#[macro_use]
+mod common;
+
+#[test]
+fn map_inline() {
+    let vec: SmallVec<[u32; 4]> = smallvec![1, 2, 3];
+
+    let vec = vec.map(|x| x as f32);
+    assert!(!vec.spilled());
+    assert_eq!(*vec, [1.0, 2.0, 3.0]);
+
+    let vec = vec.map(f64::from);
+    assert!(!vec.spilled());
+    assert_eq!(*vec, [1.0, 2.0, 3.0]);
+}
+
+#[test]
+fn map_spilled() {
+    let vec: SmallVec<[u32; 2]> = smallvec![1, 2, 3, 4];
+    assert!(vec.spilled());
+    let ptr = vec.as_ptr() as usize;
+
+    let vec = vec.map(|x| x as f32);
+    assert!(vec.spilled());
+    assert_eq!(*vec, [1.0, 2.0, 3.0, 4.0]);
+    assert_reuse!(vec.as_ptr() as usize, ptr);
+
+    let vec = vec.map(f64::from);
+    assert!(vec.spilled());
+    assert_eq!(*vec, [1.0, 2.0, 3.0, 4.0]);
+}
+
+#[test]
+fn try_map() {
+    let vec: SmallVec<[u32; 2]> = smallvec![1, 2, 3];
+    assert_eq!(vec.try_map(|x| if x == 2 { Err(x) } else { Ok(x) }), Err(2));
+
+    let vec: SmallVec<[u32; 4]> = smallvec![1, 2, 3];
+    assert_eq!(vec.try_map(|x| if x == 2 { Err(x) } else { Ok(x) }), Err(2));
+
+    let vec: SmallVec<[u32; 4]> = smallvec![1, 2, 3];
+    let vec = vec.try_map(|x| x.checked_mul(2)).ok().unwrap();
+    assert_eq!(*vec, [2, 4, 6]);
+}
+
+#[test]
+fn zip_with() {
+    let inline: SmallVec<[u32; 4]> = smallvec![1, 2, 3];
+    let spilled: SmallVec<[u32; 4]> = smallvec![10, 20, 30, 40, 50];
+
+    let vec = inline.clone().zip_with(inline.clone(), |x, y| x + y);
+    assert!(!vec.spilled());
+    assert_eq!(*vec, [2, 4, 6]);
+
+    let spilled_clone = spilled.clone();
+    let ptr = spilled_clone.as_ptr() as usize;
+    let vec = spilled_clone.zip_with(inline.clone(), |x, y| x - y);
+    assert_eq!(*vec, [9, 18, 27]);
+    assert_reuse!(vec.as_ptr() as usize, ptr);
+
+    let vec = inline.zip_with(spilled, |x, y| y - x);
+    assert_eq!(*vec, [9, 18, 27]);
+
+    let left: SmallVec<[u32; 2]> = smallvec![1, 2, 3];
+    let right: SmallVec<[f32; 2]> = smallvec![1.0, 2.0, 3.0, 4.0];
+    let ptr_right = right.as_ptr() as usize;
+    let vec = left.zip_with(right, |x, y| x as f32 * y);
+    assert_eq!(*vec, [1.0, 4.0, 9.0]);
+    assert_reuse!(vec.as_ptr() as usize, ptr_right);
+}
@@ -1,10 +1,21 @@
-use vec_utils::{try_zip_with, zip_with, VecExt};
+use std::collections::VecDeque;
+use std::mem::MaybeUninit;
+use std::ops::ControlFlow;
+
+use vec_utils::{
+    flatten_options, transpose_options, transpose_results, try_map_arc, try_map_rc, try_zip_with,
+    zip_with, ArrayExt, BoxSliceExt, Either, EitherOrBoth, Remainder, Reused, SliceExt,
+    TransparentWrapper, VecDequeExt, VecExt, VecPool, VecZip,
+};
+
+#[macro_use]
+mod common;
 
 #[test]
 fn map() {
     let vec = vec![0.0f32, 1.0, 2.0, 3.0];
 
-    let vec: Vec<u32> = vec.map(move |x| unsafe { std::mem::transmute(x) });
+    let vec: Vec<u32> = vec.map(move |x| x.to_bits());
 
     assert_eq!(
         vec,
@@ -21,7 +32,7 @@ fn map() {
 fn map_combin() {
     let vec = vec![0.0f32, 1.0, 2.0, 3.0];
 
-    let vec: Vec<u32> = zip_with!(vec, |x| unsafe { std::mem::transmute(x) });
+    let vec: Vec<u32> = zip_with!(vec, |x| x.to_bits());
 
     assert_eq!(
         vec,
@@ -146,3 +157,1983 @@ fn try_zip() {
 
     assert_eq!(vec, Err(()));
 }
+
+#[test]
+fn try_map() {
+    let vec = vec![1u32, 2, 3, 4];
+
+    let res = vec.try_map(|x| if x < 10 { Ok(x as i32) } else { Err(x) });
+
+    assert_eq!(res, Ok(vec![1, 2, 3, 4]));
+
+    let vec = vec![1u32, 2, 3, 4];
+
+    let res = vec.try_map(|x| if x < 3 { Some(x as i32) } else { None });
+
+    assert!(res.is_err());
+}
+
+#[test]
+fn map_in_place() {
+    let mut vec = vec![1.0f32, 2.0, 3.0, 4.0];
+    let ptr = vec.as_ptr();
+
+    vec.map_in_place(|x| *x /= 4.0);
+
+    assert_eq!(vec, [0.25, 0.5, 0.75, 1.0]);
+    assert_eq!(vec.as_ptr(), ptr);
+}
+
+#[test]
+fn map_diff_align() {
+    let vec = vec![[1u8, 2, 3, 4], [5, 6, 7, 8]];
+
+    let vec: Vec<u32> = vec.map(u32::from_le_bytes);
+
+    assert_eq!(vec, [0x0403_0201, 0x0807_0605]);
+
+    let vec: Vec<[u8; 4]> = vec.map(u32::to_le_bytes);
+
+    assert_eq!(vec, [[1, 2, 3, 4], [5, 6, 7, 8]]);
+}
+
+#[test]
+fn map_smaller() {
+    let vec = vec![[1u32, 2], [3, 4], [5, 6]];
+    let ptr = vec.as_ptr() as usize;
+    let cap = vec.capacity();
+
+    let vec: Vec<u32> = vec.map(|[x, y]| x + y);
+
+    assert_eq!(vec, [3, 7, 11]);
+    assert_reuse!(vec.as_ptr() as usize, ptr);
+    assert_reuse!(vec.capacity(), cap * 2);
+
+    let vec = vec![[1u32, 2, 3], [4, 5, 6], [7, 8, 9]];
+    let cap = vec.capacity();
+
+    let vec: Vec<[u32; 2]> = vec.map(|[x, y, z]| [x + y, z]);
+
+    assert_eq!(vec, [[3, 3], [9, 6], [15, 9]]);
+    assert_reuse!(vec.capacity(), cap * 3 / 2);
+}
+
+#[test]
+fn map_larger() {
+    let mut vec = Vec::with_capacity(16);
+    vec.extend(0u32..4);
+    let ptr = vec.as_ptr() as usize;
+
+    let vec: Vec<[u32; 4]> = vec.map(|x| [x, x + 1, x + 2, x + 3]);
+
+    assert_eq!(vec, [[0, 1, 2, 3], [1, 2, 3, 4], [2, 3, 4, 5], [3, 4, 5, 6]]);
+    assert_reuse!(vec.as_ptr() as usize, ptr);
+    assert_reuse!(vec.capacity(), 4);
+
+    let mut vec = Vec::with_capacity(5);
+    vec.extend(0u32..2);
+
+    let vec: Vec<[u32; 2]> = vec.map(|x| [x, x * 2]);
+
+    assert_eq!(vec, [[0, 0], [1, 2]]);
+    assert_reuse!(vec.capacity(), 2);
+}
+
+#[test]
+fn filter_map() {
+    let vec = vec![0u32, 1, 2, 3, 4, 5, 6];
+    let ptr = vec.as_ptr() as usize;
+
+    let vec: Vec<i32> = vec.filter_map(|x| if x % 2 == 0 { Some(x as i32) } else { None });
+
+    assert_eq!(vec, [0, 2, 4, 6]);
+    assert_reuse!(vec.as_ptr() as usize, ptr);
+
+    let vec: Vec<f64> = vec.filter_map(|x| if x > 0 { Some(f64::from(x)) } else { None });
+
+    assert_eq!(vec, [2.0, 4.0, 6.0]);
+}
+
+#[test]
+fn try_filter_map() {
+    #[derive(Debug, PartialEq)]
+    struct MyError(u32);
+
+    let vec = vec![0u32, 1, 2, 3, 4, 5, 6];
+    let ptr = vec.as_ptr() as usize;
+
+    let vec = vec.try_filter_map(|x| match x {
+        x if x > 10 => Err(MyError(x)),
+        x if x % 2 == 0 => Ok(Some(x)),
+        _ => Ok(None),
+    });
+
+    let vec = vec.unwrap();
+    assert_eq!(vec, [0, 2, 4, 6]);
+    assert_reuse!(vec.as_ptr() as usize, ptr);
+
+    let vec = vec![0u32, 12, 2, 15];
+
+    let vec = vec.try_filter_map(|x| match x {
+        x if x > 10 => Err(MyError(x)),
+        x if x % 2 == 0 => Ok(Some(x)),
+        _ => Ok(None),
+    });
+
+    assert_eq!(vec, Err(MyError(12)));
+}
+
+#[test]
+fn zip3_with() {
+    let r = vec![0u8, 1, 2, 3];
+    let g = vec![4u8, 5, 6];
+    let b = vec![7u8, 8, 9, 10, 11];
+
+    let vec: Vec<[u8; 3]> = r.zip3_with(g, b, |r, g, b| [r, g, b]);
+
+    assert_eq!(vec, [[0, 4, 7], [1, 5, 8], [2, 6, 9]]);
+
+    let a = vec![0u32, 1, 2, 3];
+    let b = vec![0u32; 2];
+    let mut c = Vec::with_capacity(16);
+    c.extend(0..4);
+    let ptr = c.as_ptr() as usize;
+
+    let vec: Vec<u32> = a.zip3_with(b, c, |a, b, c| a + b + c);
+
+    assert_eq!(vec, [0, 2]);
+    assert_reuse!(vec.as_ptr() as usize, ptr);
+
+    let a = vec![1.0f32, 2.0, 3.0];
+    let b = vec![1.0f32, 2.0, 3.0];
+    let c = vec![1.0f32, 2.0, 3.0];
+
+    let vec = a.try_zip3_with(b, c, |a, b, c| if a < 3.0 { Ok(a + b + c) } else { Err(a) });
+
+    assert_eq!(vec, Err(3.0));
+}
+
+#[test]
+fn zip_semicolon() {
+    let a = vec![0, 1, 2, 3];
+    let b = vec![4, 5, 6, 7];
+    let c = vec![8, 9, 10];
+    let d = vec![11, 12, 13, 14, 15];
+
+    let vec: Vec<i32> = zip_with!(a, b, c, d; |w, x, y, z| w + x + y + z);
+
+    assert_eq!(vec, [23, 27, 31]);
+
+    let a = vec![0, 1, 2, 3];
+    let b = vec![4, 5, 6, 7];
+
+    let vec = try_zip_with!(a, b; |x, y| if x < 2 { Ok(x + y) } else { Err(x) });
+
+    assert_eq!(vec, Err(2));
+
+    let a = vec![0, 1, 2, 3];
+    let offset = 10;
+
+    let vec: Vec<i32> = zip_with!(a; move |x| x + offset);
+
+    assert_eq!(vec, [10, 11, 12, 13]);
+}
+
+#[test]
+fn map_indexed() {
+    let vec = vec![1.0f32, 1.0, 1.0, 1.0];
+    let ptr = vec.as_ptr() as usize;
+
+    let vec: Vec<f32> = vec.map_indexed(|i, x| x * i as f32);
+
+    assert_eq!(vec, [0.0, 1.0, 2.0, 3.0]);
+    assert_reuse!(vec.as_ptr() as usize, ptr);
+
+    let vec: Vec<(usize, f32)> = vec.map_indexed(|i, x| (i, x));
+
+    assert_eq!(vec, [(0, 0.0), (1, 1.0), (2, 2.0), (3, 3.0)]);
+}
+
+#[test]
+fn try_map_indexed() {
+    #[derive(Debug, PartialEq)]
+    struct ParseError {
+        index: usize,
+    }
+
+    let vec = vec!["1", "2", "x", "4"];
+
+    let res: Result<Vec<u32>, _> =
+        vec.try_map_indexed(|index, x| x.parse().map_err(|_| ParseError { index }));
+
+    assert_eq!(res, Err(ParseError { index: 2 }));
+
+    let vec = vec!["1", "2", "3", "4"];
+
+    let res: Result<Vec<u32>, _> =
+        vec.try_map_indexed(|index, x| x.parse().map_err(|_| ParseError { index }));
+
+    assert_eq!(res, Ok(vec![1, 2, 3, 4]));
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn par_map() {
+    let vec = (0..10_000u32).collect::<Vec<_>>();
+    let ptr = vec.as_ptr() as usize;
+
+    let vec: Vec<i32> = vec.par_map(|x| x as i32 * 2);
+
+    assert_eq!(vec, (0..10_000).map(|x| x * 2).collect::<Vec<_>>());
+    assert_reuse!(vec.as_ptr() as usize, ptr);
+
+    let vec: Vec<f64> = vec.par_map(f64::from);
+
+    assert_eq!(
+        vec,
+        (0..10_000).map(|x| f64::from(x * 2)).collect::<Vec<_>>()
+    );
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn par_map_in_pool() {
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(2)
+        .build()
+        .unwrap();
+
+    let vec = (0..10_000u32).collect::<Vec<_>>();
+    let ptr = vec.as_ptr() as usize;
+
+    let vec: Vec<i32> = vec.par_map_in_pool(&pool, |x| {
+        assert!(pool.current_thread_index().is_some());
+        x as i32 * 2
+    });
+
+    assert_eq!(vec, (0..10_000).map(|x| x * 2).collect::<Vec<_>>());
+    assert_reuse!(vec.as_ptr() as usize, ptr);
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn par_zip_with() {
+    let a = (0..10_000u32).collect::<Vec<_>>();
+    let b = (0..5_000u32).map(|x| x as f32).collect::<Vec<_>>();
+    let ptr = a.as_ptr() as usize;
+
+    let vec: Vec<i32> = a.par_zip_with(b, |x, y| x as i32 + y as i32);
+
+    assert_eq!(vec, (0..5_000).map(|x| x * 2).collect::<Vec<_>>());
+    assert_reuse!(vec.as_ptr() as usize, ptr);
+
+    // the right vector has the larger capacity, so it's allocation is reused
+    let mut b = Vec::with_capacity(20_000);
+    b.extend(0..10_000u32);
+    let ptr = b.as_ptr() as usize;
+
+    let vec: Vec<u32> = vec.par_zip_with(b, |x, y| x as u32 + y);
+
+    assert_eq!(vec, (0..5_000).map(|x| x * 3).collect::<Vec<_>>());
+    assert_reuse!(vec.as_ptr() as usize, ptr);
+}
+
+#[test]
+fn box_slice_map() {
+    let bx: Box<[u32]> = vec![1, 2, 3, 4].into_boxed_slice();
+    let ptr = bx.as_ptr() as usize;
+
+    let bx: Box<[i32]> = bx.map(|x| -(x as i32));
+
+    assert_eq!(*bx, [-1, -2, -3, -4]);
+    assert_reuse!(bx.as_ptr() as usize, ptr);
+
+    let bx: Box<[f64]> = bx.map(f64::from);
+
+    assert_eq!(*bx, [-1.0, -2.0, -3.0, -4.0]);
+
+    let bx: Box<[()]> = vec![(); 10].into_boxed_slice();
+    let mut count = 0;
+
+    let bx: Box<[()]> = bx.map(|()| count += 1);
+
+    assert_eq!(bx.len(), 10);
+    assert_eq!(count, 10);
+}
+
+#[test]
+fn box_slice_zip_with() {
+    let a: Box<[u32]> = vec![1, 2, 3, 4].into_boxed_slice();
+    let b: Box<[u32]> = vec![5, 6, 7].into_boxed_slice();
+
+    let bx: Box<[u32]> = a.zip_with(b, |a, b| a * b);
+
+    assert_eq!(*bx, [5, 12, 21]);
+
+    let a: Box<[u32]> = vec![1, 2, 3, 4].into_boxed_slice();
+    let b: Box<[u32]> = vec![5, 6, 7, 8].into_boxed_slice();
+
+    let bx = a.try_zip_with(b, |a, b| if a < 3 { Ok(a * b) } else { Err(a) });
+
+    assert_eq!(bx, Err(3));
+}
+
+#[test]
+fn vec_deque_map() {
+    let mut deque = VecDeque::with_capacity(8);
+    deque.extend(0u64..6);
+    // rotate the deque so that it wraps around the end of the buffer
+    for _ in 0..4 {
+        let x = deque.pop_front().unwrap();
+        deque.push_back(x);
+    }
+    assert!(!deque.as_slices().1.is_empty());
+
+    let cap = deque.capacity();
+    let deque: VecDeque<i64> = deque.map(|x| -(x as i64));
+
+    assert_eq!(deque, [-4, -5, 0, -1, -2, -3]);
+    assert_eq!(deque.capacity(), cap);
+
+    let other = VecDeque::from(vec![1i64, 2, 3]);
+    let deque: VecDeque<i64> = deque.zip_with(other, |x, y| x * y);
+
+    assert_eq!(deque, [-4, -10, 0]);
+}
+
+#[test]
+fn map_to_vec() {
+    let data = [1u32, 2, 3, 4];
+
+    let vec: Vec<String> = data.map_to_vec(|x| x.to_string());
+
+    assert_eq!(vec, ["1", "2", "3", "4"]);
+    assert_eq!(vec.capacity(), 4);
+
+    let vec: Result<Vec<u32>, _> = vec.try_map_to_vec(|x| x.parse::<u32>());
+
+    assert_eq!(vec, Ok(data.to_vec()));
+
+    let vec = ["1", "x"].try_map_to_vec(|x| x.parse::<u32>().ok());
+
+    assert!(vec.is_err());
+}
+
+#[test]
+fn zip_with_remainder() {
+    let a = vec![1u32, 2, 3, 4, 5];
+    let b = vec![10u32, 20, 30];
+
+    let (vec, rem) = a.zip_with_remainder(b, |a, b| a + b);
+
+    assert_eq!(vec, [11, 22, 33]);
+    assert_eq!(rem, Remainder::Left(vec![4, 5]));
+
+    let a = vec![1u32, 2];
+    let b = vec![10.0f64, 20.0, 30.0];
+
+    let (vec, rem) = a.zip_with_remainder(b, |a, b| f64::from(a) + b);
+
+    assert_eq!(vec, [11.0, 22.0]);
+    assert_eq!(rem, Remainder::Right(vec![30.0]));
+
+    let a = vec![1u32, 2];
+    let b = vec![3u32, 4];
+
+    let (vec, rem) = a.zip_with_remainder(b, |a, b| a * b);
+
+    assert_eq!(vec, [3, 8]);
+    assert_eq!(rem, Remainder::Neither);
+}
+
+#[test]
+fn zip_eq_with() {
+    let a = vec![1u32, 2, 3];
+    let b = vec![4u32, 5, 6];
+
+    let vec: Vec<u32> = a.zip_eq_with(b, |a, b| a * b);
+
+    assert_eq!(vec, [4, 10, 18]);
+
+    let a = vec![1u32, 2, 3];
+    let b = vec![4u32, 5, 6];
+
+    let vec = a.try_zip_eq_with(b, |a, b| if a < 2 { Ok(a * b) } else { Err(a) });
+
+    assert_eq!(vec, Err(2));
+}
+
+#[test]
+#[should_panic(expected = "left: 3, right: 2")]
+fn zip_eq_with_mismatch() {
+    let a = vec![1u32, 2, 3];
+    let b = vec![4u32, 5];
+
+    a.zip_eq_with(b, |a, b| a * b);
+}
+
+#[test]
+fn map_into() {
+    let mut out = Vec::new();
+
+    vec![1u8, 2, 3].map_into(&mut out, u32::from);
+    vec![4u16, 5].map_into(&mut out, u32::from);
+    vec![6u32].map_into(&mut out, |x| x);
+
+    assert_eq!(out, [1, 2, 3, 4, 5, 6]);
+}
+
+#[test]
+fn unzip_with() {
+    let vec = vec![1u32, 2, 3];
+    let ptr = vec.as_ptr() as usize;
+
+    let (a, b): (Vec<i32>, Vec<f64>) = vec.unzip_with(|x| (x as i32, f64::from(x) / 2.0));
+
+    assert_eq!(a, [1, 2, 3]);
+    assert_eq!(b, [0.5, 1.0, 1.5]);
+    assert_reuse!(a.as_ptr() as usize, ptr);
+
+    let vec = vec![1u32, 2, 3];
+    let ptr = vec.as_ptr() as usize;
+
+    let (a, b): (Vec<f64>, Vec<i32>) = vec.unzip_with(|x| (f64::from(x) / 2.0, x as i32));
+
+    assert_eq!(a, [0.5, 1.0, 1.5]);
+    assert_eq!(b, [1, 2, 3]);
+    assert_reuse!(b.as_ptr() as usize, ptr);
+}
+
+#[test]
+fn partition_map() {
+    let vec = vec![1u32, 2, 3, 4, 5];
+    let ptr = vec.as_ptr() as usize;
+
+    let (even, odd): (Vec<f32>, Vec<u8>) = vec.partition_map(|x| {
+        if x % 2 == 0 {
+            Either::Left(x as f32)
+        } else {
+            Either::Right(x as u8)
+        }
+    });
+
+    assert_eq!(even, [2.0, 4.0]);
+    assert_eq!(odd, [1, 3, 5]);
+    assert_reuse!(even.as_ptr() as usize, ptr);
+}
+
+#[test]
+fn flat_map() {
+    let vec = vec![0u32, 1, 2, 3];
+    let ptr = vec.as_ptr() as usize;
+
+    // each input expands to at most one output, so everything fits in place
+    let vec: Vec<i32> = vec.flat_map(|x| if x % 2 == 0 { None } else { Some(x as i32) });
+
+    assert_eq!(vec, [1, 3]);
+    assert_reuse!(vec.as_ptr() as usize, ptr);
+
+    // each input expands to many outputs, so the output must grow
+    let vec = vec![0u32, 1, 2, 3];
+    let vec: Vec<i32> = vec.flat_map(|x| (0..x as i32).map(move |y| y * 10 + x as i32));
+
+    assert_eq!(vec, [1, 2, 12, 3, 13, 23]);
+
+    let vec = vec![1u32, 2, 3];
+    let vec: Vec<f64> = vec.flat_map(|x| vec![f64::from(x); x as usize]);
+
+    assert_eq!(vec, [1.0, 2.0, 2.0, 3.0, 3.0, 3.0]);
+}
+
+#[test]
+fn map_while() {
+    let vec = vec![1u32, 2, 3, 0, 4, 5];
+    let ptr = vec.as_ptr() as usize;
+    let cap = vec.capacity();
+
+    let vec: Vec<i32> = vec.map_while(|x| if x == 0 { None } else { Some(x as i32) });
+
+    assert_eq!(vec, [1, 2, 3]);
+    assert_reuse!(vec.as_ptr() as usize, ptr);
+    assert_eq!(vec.capacity(), cap);
+
+    let vec = vec![1u32, 2, 3];
+    let vec: Vec<f64> = vec.map_while(|x| Some(f64::from(x)));
+
+    assert_eq!(vec, [1.0, 2.0, 3.0]);
+}
+
+#[test]
+fn drop_and_reuse() {
+    let vec = Vec::<u32>::with_capacity(10);
+    let ptr = vec.as_ptr() as usize;
+
+    let vec = vec.drop_and_reuse::<i32>();
+    assert_eq!(vec.capacity(), 10);
+    assert_reuse!(vec.as_ptr() as usize, ptr);
+
+    // 40 bytes hold 3 `[u32; 3]`s, the left over bytes are shrunk off
+    let vec = vec.drop_and_reuse::<[u32; 3]>();
+    assert_eq!(vec.capacity(), 3);
+    assert!(vec.is_empty());
+
+    let vec = vec.drop_and_reuse::<f32>();
+    assert_eq!(vec.capacity(), 9);
+
+    // the alignments don't match
+    let vec = vec.drop_and_reuse::<u8>();
+    assert_eq!(vec.capacity(), 0);
+}
+
+#[test]
+fn array_map() {
+    let array = [1u32, 2, 3, 4];
+
+    assert_eq!(array.map_array(|x| x as i32 - 2), [-1, 0, 1, 2]);
+    assert_eq!(array.map_array(f64::from), [1.0, 2.0, 3.0, 4.0]);
+    assert_eq!(array.map_array(|x| x as u8), [1, 2, 3, 4]);
+
+    assert_eq!(
+        array.try_map_array(|x| x.checked_sub(1)).ok(),
+        Some([0, 1, 2, 3])
+    );
+    assert!(array.try_map_array(|x| x.checked_sub(2)).is_err());
+}
+
+#[test]
+fn map_zst() {
+    #[derive(Debug, PartialEq)]
+    struct Zst;
+
+    let vec = vec![(); 10];
+    let vec = vec.map(|()| Zst);
+    assert_eq!(vec.len(), 10);
+
+    let vec = vec.map_indexed(|i, Zst| i);
+    assert_eq!(vec, (0..10).collect::<Vec<_>>());
+
+    let vec = vec![(); 10];
+    let vec = vec.try_map(|()| Some(Zst)).unwrap_or_default();
+    assert_eq!(vec, [Zst, Zst, Zst, Zst, Zst, Zst, Zst, Zst, Zst, Zst]);
+}
+
+#[test]
+fn zip_zst() {
+    let a = vec![(); 10];
+    let b = vec![(); 4];
+
+    let vec = a.zip_with(b, |(), ()| ());
+    assert_eq!(vec.len(), 4);
+
+    let a = vec![(); 3];
+    let b = vec![(); 7];
+
+    let vec = a.zip_with(b, |(), ()| 1u8);
+    assert_eq!(vec, [1, 1, 1]);
+}
+
+#[test]
+fn map_empty() {
+    let vec = Vec::<u32>::new();
+    assert_eq!(vec.map(|x| x as i32), Vec::<i32>::new());
+
+    let vec = Vec::<u32>::new();
+    assert_eq!(vec.map(f64::from), Vec::<f64>::new());
+
+    let vec = Vec::<u32>::with_capacity(8);
+    let vec = vec.map(|x| x as i32);
+    assert!(vec.is_empty());
+    assert_eq!(vec.capacity(), 8);
+
+    let vec = Vec::<u32>::new();
+    assert_eq!(vec.try_map(|_| Err::<u32, _>(())), Ok(Vec::new()));
+
+    let vec = vec![1u32];
+    assert_eq!(vec.try_map(|_| Err::<u32, _>(())), Err(()));
+
+    let vec = Vec::<u32>::new();
+    assert_eq!(vec.zip_with(Vec::<f32>::new(), |x, _| x), Vec::<u32>::new());
+
+    let vec = Vec::<u32>::new();
+    assert_eq!(vec.zip_with(vec![1.0f32], |x, _| x), Vec::<u32>::new());
+
+    let vec = vec![1u32];
+    assert_eq!(
+        vec.try_zip_with(vec![1u32], |_, _| Err::<u32, _>(())),
+        Err(())
+    );
+}
+
+#[test]
+fn into_map_iter() {
+    let vec = vec![1u32, 2, 3, 4, 5];
+    let mut iter = vec.into_map_iter(|x| x as i32 * 2);
+
+    assert_eq!(iter.len(), 5);
+    assert_eq!(iter.next(), Some(2));
+    assert_eq!(iter.size_hint(), (4, Some(4)));
+    assert_eq!(iter.into_vec(), [4, 6, 8, 10]);
+
+    let vec = vec![1u32, 2, 3, 4, 5];
+    let ptr = vec.as_ptr() as usize;
+    let vec = vec.into_map_iter(|x| x as f32).into_vec();
+    assert_eq!(vec, [1.0, 2.0, 3.0, 4.0, 5.0]);
+    assert_reuse!(vec.as_ptr() as usize, ptr);
+
+    let vec = vec![1u32, 2, 3];
+    let sum: f64 = vec.into_map_iter(f64::from).sum();
+    assert_eq!(sum, 6.0);
+}
+
+#[test]
+fn into_map_iter_collect_allocates() {
+    // `into_vec` maps in place
+    let vec = vec![1u32, 2, 3, 4];
+    let ptr = vec.as_ptr() as usize;
+    let vec = vec.into_map_iter(|x| x as f32).into_vec();
+    assert_reuse!(vec.as_ptr() as usize, ptr);
+
+    // but `collect` allocates a new vector while the input is still alive
+    let vec = vec![1u32, 2, 3, 4];
+    let ptr = vec.as_ptr() as usize;
+    let vec = vec.into_map_iter(|x| x as f32).collect::<Vec<_>>();
+    assert_eq!(vec, [1.0, 2.0, 3.0, 4.0]);
+    assert_ne!(vec.as_ptr() as usize, ptr);
+}
+
+#[test]
+fn into_map_iter_double_ended() {
+    let vec = vec![1u32, 2, 3, 4, 5, 6];
+    let ptr = vec.as_ptr() as usize;
+    let mut iter = vec.into_map_iter(|x| x as f32);
+
+    assert_eq!(iter.next_back(), Some(6.0));
+    assert_eq!(iter.next(), Some(1.0));
+    assert_eq!(iter.next_back(), Some(5.0));
+    assert_eq!(iter.len(), 3);
+
+    let vec = iter.into_vec();
+    assert_eq!(vec, [2.0, 3.0, 4.0]);
+    assert_reuse!(vec.as_ptr() as usize, ptr);
+
+    let vec = vec![1u32, 2, 3, 4];
+    let mut iter = vec.into_map_iter(|x| x * 10);
+    assert_eq!(iter.next(), Some(10));
+    assert_eq!(iter.next_back(), Some(40));
+    assert_eq!(iter.next_back(), Some(30));
+    assert_eq!(iter.next(), Some(20));
+    assert_eq!(iter.next(), None);
+    assert_eq!(iter.next_back(), None);
+    assert!(iter.into_vec().is_empty());
+
+    let vec = vec![1u8, 2, 3];
+    let rev = vec.into_map_iter(u64::from).rev().collect::<Vec<_>>();
+    assert_eq!(rev, [3, 2, 1]);
+}
+
+#[test]
+fn chunks_map() {
+    let vec = (0u32..10).collect::<Vec<_>>();
+    let ptr = vec.as_ptr() as usize;
+
+    let (vec, rem) = vec.chunks_map(|[a, b, c]: [u32; 3]| a + b + c);
+    assert_eq!(vec, [3, 12, 21]);
+    assert_eq!(rem, [9]);
+    assert_reuse!(vec.as_ptr() as usize, ptr);
+
+    let vec = (0u32..8).collect::<Vec<_>>();
+    let (vec, rem) = vec.chunks_map(|[a, b]: [u32; 2]| [a, b, a + b]);
+    assert_eq!(vec, [[0, 1, 1], [2, 3, 5], [4, 5, 9], [6, 7, 13]]);
+    assert!(rem.is_empty());
+
+    let vec = (0u8..5).collect::<Vec<_>>();
+    let (vec, rem) = vec.chunks_map(|[a, b]: [u8; 2]| f64::from(a) * f64::from(b));
+    assert_eq!(vec, [0.0, 6.0]);
+    assert_eq!(rem, [4]);
+}
+
+#[test]
+fn chunks_map_larger_alignment() {
+    // `f64` is more aligned than `u8`, so the output is collected into a new allocation
+    let vec = (0u8..9).collect::<Vec<_>>();
+    let (vec, rem) = vec.chunks_map(|[a, b, c, d]: [u8; 4]| f64::from(a + b + c + d));
+    assert_eq!(vec, [6.0, 22.0]);
+    assert_eq!(rem, [8]);
+
+    let vec = (0u8..9).collect::<Vec<_>>();
+    let res = std::panic::catch_unwind(|| {
+        vec.chunks_map(|[a, ..]: [u8; 4]| {
+            if a == 4 {
+                panic!("stop");
+            }
+
+            f64::from(a)
+        })
+    });
+    assert!(res.is_err());
+}
+
+#[test]
+#[should_panic]
+fn chunks_map_zero() {
+    let vec = vec![1u32, 2, 3];
+    let _ = vec.chunks_map(|[]: [u32; 0]| 0u32);
+}
+
+#[test]
+fn windows_map() {
+    let vec = (0u32..6).collect::<Vec<_>>();
+    let ptr = vec.as_ptr() as usize;
+
+    let vec = vec.windows_map(3, |w| w.iter().sum::<u32>() as i32);
+    assert_eq!(vec, [3, 6, 9, 12]);
+    assert_reuse!(vec.as_ptr() as usize, ptr);
+
+    let vec = (0u32..6).collect::<Vec<_>>();
+    let vec = vec.windows_map(2, |w| f64::from(w[0] + w[1]));
+    assert_eq!(vec, [1.0, 3.0, 5.0, 7.0, 9.0]);
+
+    let vec = (0u32..3).collect::<Vec<_>>();
+    let vec = vec.windows_map(4, |w| w.len());
+    assert!(vec.is_empty());
+}
+
+#[test]
+#[should_panic]
+fn windows_map_zero() {
+    let vec = vec![1u32, 2, 3];
+    let _ = vec.windows_map(0, |w| w.len());
+}
+
+#[test]
+fn zip_with_index() {
+    let vec = vec![10u32, 20, 30, 40];
+    let ptr = vec.as_ptr() as usize;
+
+    let vec = vec.zip_with_index(|i, x| x as i32 + i as i32);
+    assert_eq!(vec, [10, 21, 32, 43]);
+    assert_reuse!(vec.as_ptr() as usize, ptr);
+
+    let vec = vec.zip_with_index(|i, x| (i, x));
+    assert_eq!(vec, [(0, 10), (1, 21), (2, 32), (3, 43)]);
+
+    let vec = vec![1u32, 2, 3];
+    let res = vec.try_zip_with_index(|i, x| if i < 2 { Ok(x) } else { Err(i) });
+    assert_eq!(res, Err(2));
+}
+
+#[test]
+fn scan_map() {
+    let vec = vec![1u32, 2, 3, 4];
+    let ptr = vec.as_ptr() as usize;
+
+    let vec = vec.scan_map(0, |sum, x| {
+        *sum += x as i32;
+        *sum
+    });
+    assert_eq!(vec, [1, 3, 6, 10]);
+    assert_reuse!(vec.as_ptr() as usize, ptr);
+
+    let vec = vec.scan_map(None, |prev, x| {
+        let diff = prev.map_or(x, |prev| x - prev);
+        *prev = Some(x);
+        f64::from(diff)
+    });
+    assert_eq!(vec, [1.0, 2.0, 3.0, 4.0]);
+}
+
+#[test]
+fn retain_map() {
+    let vec = vec![String::from("a"), String::new(), String::from("bc")];
+    let ptr = vec.as_ptr() as usize;
+
+    let vec = vec.retain_map(|mut x| {
+        if x.is_empty() {
+            None
+        } else {
+            x.push('!');
+            Some(x)
+        }
+    });
+
+    assert_eq!(vec, ["a!", "bc!"]);
+    assert_reuse!(vec.as_ptr() as usize, ptr);
+}
+
+#[test]
+fn dedup_map() {
+    let vec = vec![1u32, 2, 3, 4, 5, 6, 7, 1, 2];
+    let ptr = vec.as_ptr() as usize;
+
+    // collapses the runs of values with the same result of `x / 3`
+    let vec = vec.dedup_map(|x| x as i32, |x| x / 3);
+    assert_eq!(vec, [1, 3, 6, 1]);
+    assert_reuse!(vec.as_ptr() as usize, ptr);
+
+    let vec = vec![1u32, 1, 2, 2, 2, 1];
+    let vec = vec.dedup_map(f64::from, |&x| x);
+    assert_eq!(vec, [1.0, 2.0, 1.0]);
+}
+
+#[test]
+fn map_catch_unwind() {
+    let vec = vec![1u32, 2, 3, 4];
+    let res = vec.map_catch_unwind(|x| x as i32);
+    assert_eq!(res.ok(), Some(vec![1, 2, 3, 4]));
+
+    let vec = vec![1u32, 2, 3, 4];
+    let ptr = vec.as_ptr() as usize;
+
+    let res = vec.map_catch_unwind(|x| {
+        if x == 3 {
+            panic!("three");
+        }
+
+        x as i32
+    });
+
+    let (vec, payload) = res.unwrap_err();
+    assert_eq!(vec, [1, 2]);
+    assert_reuse!(vec.as_ptr() as usize, ptr);
+    assert_eq!(payload.downcast_ref::<&str>(), Some(&"three"));
+}
+
+#[test]
+fn try_map_resumable() {
+    let vec = vec![1u32, 2, 3];
+    let res = vec.try_map_resumable(|x| Ok::<_, ()>(x as i32));
+    assert_eq!(res, Ok(vec![1, 2, 3]));
+
+    let vec = vec![1u32, 2, 0, 4, 5];
+    let res = vec.try_map_resumable(|x| if x == 0 { Err("zero") } else { Ok(x as i32) });
+    assert_eq!(res, Err((vec![1, 2], "zero", vec![4, 5])));
+
+    // this can't reuse the allocation
+    let vec = vec![1u8, 0, 3];
+    let res = vec.try_map_resumable(|x| {
+        if x == 0 {
+            Err("zero")
+        } else {
+            Ok(u64::from(x))
+        }
+    });
+    assert_eq!(res, Err((vec![1], "zero", vec![3])));
+}
+
+#[test]
+fn convert() {
+    let vec = vec![1u32, 2, 3];
+    let vec = vec.convert::<u64>();
+    assert_eq!(vec, [1, 2, 3]);
+
+    let vec = vec![1u8, 2, 3];
+    let ptr = vec.as_ptr() as usize;
+    let vec = vec.convert::<u8>();
+    assert_eq!(vec, [1, 2, 3]);
+    assert_reuse!(vec.as_ptr() as usize, ptr);
+
+    let vec = vec![1u32, 2, 3];
+    let vec = vec.try_convert::<u8>();
+    assert_eq!(vec, Ok(vec![1, 2, 3]));
+
+    let vec = vec![1u32, 300, 3];
+    assert!(vec.try_convert::<u8>().is_err());
+
+    let vec = vec![1u32, 2, 3];
+    let ptr = vec.as_ptr() as usize;
+    let vec = vec.try_convert::<i32>().unwrap();
+    assert_eq!(vec, [1, 2, 3]);
+    assert_reuse!(vec.as_ptr() as usize, ptr);
+}
+
+#[test]
+fn zip_with_reuses_largest_capacity() {
+    let mut a = Vec::<u32>::with_capacity(10);
+    let mut b = Vec::<u32>::with_capacity(20);
+    a.extend(0..5);
+    b.extend(0..5);
+    let vec = a.zip_with(b, |x, y| x + y);
+    assert_eq!(vec, [0, 2, 4, 6, 8]);
+    assert_reuse!(vec.capacity(), 20);
+
+    // incompatible inputs are never picked, even if they are larger
+    let mut a = Vec::<u32>::with_capacity(10);
+    let mut b = Vec::<u64>::with_capacity(50);
+    a.extend(0..5);
+    b.extend(0..5);
+    let vec = a.zip_with(b, |x, y| x + y as u32);
+    assert_eq!(vec, [0, 2, 4, 6, 8]);
+    assert_reuse!(vec.capacity(), 10);
+}
+
+#[test]
+fn zip3_with_reuses_largest_capacity() {
+    let mut a = Vec::<u32>::with_capacity(10);
+    let mut b = Vec::<u64>::with_capacity(40);
+    let mut c = Vec::<u32>::with_capacity(30);
+    a.extend(0..5);
+    b.extend(0..5);
+    c.extend(0..5);
+    let ptr = c.as_ptr() as usize;
+    let vec = a.zip3_with(b, c, |x, y, z| x + y as u32 + z);
+    assert_eq!(vec, [0, 3, 6, 9, 12]);
+    assert_reuse!(vec.capacity(), 30);
+    assert_reuse!(vec.as_ptr() as usize, ptr);
+
+    let mut a = Vec::<u32>::with_capacity(30);
+    let mut b = Vec::<u32>::with_capacity(10);
+    let mut c = Vec::<u32>::with_capacity(30);
+    a.extend(0..5);
+    b.extend(0..5);
+    c.extend(0..5);
+    let ptr = a.as_ptr() as usize;
+    let vec = a.zip3_with(b, c, |x, y, z| x + y + z);
+    assert_reuse!(vec.capacity(), 30);
+    assert_reuse!(vec.as_ptr() as usize, ptr);
+}
+
+#[test]
+fn with_scratch() {
+    let mut vec = Vec::<u64>::with_capacity(10);
+    vec.extend(0..2);
+    let start = vec.as_ptr() as usize;
+
+    let sum = vec.with_scratch::<u32, _, _>(|scratch| {
+        assert_eq!(scratch.capacity(), 16);
+        assert!(scratch.as_ptr() as usize >= start + 16);
+
+        for i in 0..16 {
+            scratch.push(i);
+        }
+        assert_eq!(scratch.try_push(16), Err(16));
+        assert_eq!(scratch.pop(), Some(15));
+        scratch.iter().sum::<u32>()
+    });
+
+    assert_eq!(sum, 105);
+    assert_eq!(vec, [0, 1]);
+    assert_eq!(vec.capacity(), 10);
+    assert_eq!(vec.as_ptr() as usize, start);
+
+    // the alignment of `u32` is too large for the spare capacity of a `Vec<u8>`
+    let mut vec = Vec::<u8>::with_capacity(16);
+    vec.with_scratch::<u32, _, _>(|scratch| {
+        assert_eq!(scratch.capacity(), 0);
+        assert_eq!(scratch.try_push(1), Err(1));
+    });
+
+    // a full vector has no spare capacity
+    let mut vec = vec![1u32, 2, 3];
+    vec.shrink_to_fit();
+    vec.with_scratch::<u16, _, _>(|scratch| assert_eq!(scratch.capacity(), 0));
+}
+
+#[test]
+fn map_control_flow() {
+    let vec = vec![1u32, 2, 3];
+    let ptr = vec.as_ptr() as usize;
+    let vec = vec.map_control_flow(|x| ControlFlow::<(), _>::Continue(x as i32 * 2));
+    let vec = vec.unwrap();
+    assert_eq!(vec, [2, 4, 6]);
+    assert_reuse!(vec.as_ptr() as usize, ptr);
+
+    let vec = vec![1u32, 2, 0, 3];
+    let res = vec.map_control_flow(|x| match x {
+        0 => ControlFlow::Break("found the sentinel"),
+        x => ControlFlow::Continue(x + 1),
+    });
+    assert_eq!(res, Err("found the sentinel"));
+}
+
+#[test]
+fn zip_with_capacity_matrix() {
+    fn zip<T: Copy, U: Copy, V>(
+        (left_cap, right_cap): (usize, usize),
+        (x, y): (T, U),
+        f: fn(T, U) -> V,
+    ) -> (Vec<V>, usize, usize) {
+        let mut left = Vec::with_capacity(left_cap);
+        let mut right = Vec::with_capacity(right_cap);
+        left.extend([x; 4].iter().copied());
+        right.extend([y; 4].iter().copied());
+        let (left_ptr, right_ptr) = (left.as_ptr() as usize, right.as_ptr() as usize);
+        (left.zip_with(right, f), left_ptr, right_ptr)
+    }
+
+    for &caps in &[(10, 20), (20, 10)] {
+        let max = caps.0.max(caps.1);
+
+        // both inputs are compatible, so the larger one is reused
+        let (vec, left, right) = zip(caps, (1u32, 2u32), |x, y| x + y);
+        assert_eq!(vec, [3; 4]);
+        assert_reuse!(vec.capacity(), max);
+        let reused = if caps.0 >= caps.1 { left } else { right };
+        assert_reuse!(vec.as_ptr() as usize, reused);
+
+        // only the left input is compatible
+        let (vec, left, _) = zip(caps, (1u32, 2u64), |x, y| x + y as u32);
+        assert_eq!(vec, [3; 4]);
+        assert_reuse!(vec.capacity(), caps.0);
+        assert_reuse!(vec.as_ptr() as usize, left);
+
+        // only the right input is compatible
+        let (vec, _, right) = zip(caps, (1u64, 2u32), |x, y| x as u32 + y);
+        assert_eq!(vec, [3; 4]);
+        assert_reuse!(vec.capacity(), caps.1);
+        assert_reuse!(vec.as_ptr() as usize, right);
+
+        // neither input is compatible, so a new allocation is made
+        let (vec, _, _) = zip(caps, (1u64, 2u16), |x, y| x as u32 + y as u32);
+        assert_eq!(vec, [3; 4]);
+        assert!(vec.capacity() >= 4);
+    }
+}
+
+#[test]
+fn map_into_boxed_slice() {
+    let vec = vec![1u32, 2, 3];
+    let ptr = vec.as_ptr() as usize;
+    let bx = vec.map_into_boxed_slice(|x| x as f32);
+    assert_eq!(*bx, [1.0, 2.0, 3.0]);
+    assert_reuse!(bx.as_ptr() as usize, ptr);
+
+    let mut vec = Vec::with_capacity(10);
+    vec.extend(0u32..3);
+    let bx = vec.map_into_boxed_slice(|x| x + 1);
+    assert_eq!(*bx, [1, 2, 3]);
+    assert_eq!(bx.into_vec().capacity(), 3);
+}
+
+#[test]
+fn transpose() {
+    let vec = vec![Some(Box::new(1)), Some(Box::new(2))];
+    let ptr = vec.as_ptr() as usize;
+    let vec = transpose_options(vec).unwrap();
+    assert_eq!(vec, [Box::new(1), Box::new(2)]);
+    assert_reuse!(vec.as_ptr() as usize, ptr);
+
+    let vec = vec![Some(1u32), None, Some(3)];
+    assert_eq!(transpose_options(vec), None);
+
+    let vec = vec![Ok::<_, ()>(1u8), Ok(2)];
+    assert_eq!(transpose_results(vec), Ok(vec![1, 2]));
+
+    let vec = vec![Ok(1u64), Err("error"), Err("other error")];
+    assert_eq!(transpose_results(vec), Err("error"));
+}
+
+#[test]
+fn zip_with_iter() {
+    let vec = vec![1u32, 2, 3, 4];
+    let ptr = vec.as_ptr() as usize;
+    let vec = vec.zip_with_iter(&[10, 20], |x, y| x + y);
+    assert_eq!(vec, [11, 22]);
+    assert_reuse!(vec.as_ptr() as usize, ptr);
+
+    let vec = vec![1u32, 2];
+    let vec = vec.zip_with_iter(1.., |x, y| x as f32 * y as f32);
+    assert_eq!(vec, [1.0, 4.0]);
+}
+
+#[test]
+fn map_into_slice() {
+    let mut out = [0.0f32; 3];
+
+    vec![1u32, 2, 3].map_into_slice(&mut out, |x| x as f32);
+    assert_eq!(out, [1.0, 2.0, 3.0]);
+
+    vec![4u32, 5, 6].map_into_slice(&mut out, |x| x as f32 / 2.0);
+    assert_eq!(out, [2.0, 2.5, 3.0]);
+}
+
+#[test]
+#[should_panic(expected = "Tried to map into a slice of a different length, vec: 2, slice: 3")]
+fn map_into_slice_mismatch() {
+    vec![1u32, 2].map_into_slice(&mut [0; 3], |x| x);
+}
+
+#[test]
+fn map_into_uninit() {
+    let mut out = [MaybeUninit::<f32>::uninit(); 5];
+
+    let init = vec![1u32, 2, 3].map_into_uninit(&mut out, |x| x as f32);
+    assert_eq!(init, [1.0, 2.0, 3.0]);
+    init[0] = 4.0;
+
+    let init = vec![].map_into_uninit(&mut out, |x: u32| x as f32);
+    assert!(init.is_empty());
+}
+
+#[test]
+#[should_panic(expected = "Tried to map into a slice that is too short, vec: 3, slice: 2")]
+fn map_into_uninit_too_short() {
+    let mut out = [MaybeUninit::<u32>::uninit(); 2];
+    vec![1u32, 2, 3].map_into_uninit(&mut out, |x| x);
+}
+
+#[test]
+fn map_nested() {
+    let vec = vec![vec![1u32, 2], vec![], vec![3, 4, 5]];
+    let outer = vec.as_ptr() as usize;
+    let inner = vec[2].as_ptr() as usize;
+
+    let vec = vec.map_nested(|x| x as f32 * 0.5);
+    assert_eq!(vec, [vec![0.5, 1.0], vec![], vec![1.5, 2.0, 2.5]]);
+    assert_reuse!(vec.as_ptr() as usize, outer);
+    assert_reuse!(vec[2].as_ptr() as usize, inner);
+}
+
+#[test]
+fn try_map_alloc() {
+    let vec = vec![1u32, 2, 3];
+    let ptr = vec.as_ptr() as usize;
+    let vec = vec.try_map_alloc(|x| x as f32).unwrap();
+    assert_eq!(vec, [1.0, 2.0, 3.0]);
+    assert_reuse!(vec.as_ptr() as usize, ptr);
+
+    let vec = vec![1u8, 2, 3];
+    let vec = vec.try_map_alloc(u64::from).unwrap();
+    assert_eq!(vec, [1, 2, 3]);
+
+    // zero-sized values don't allocate, but the output can never fit in memory
+    let vec = vec![(); usize::MAX];
+    assert!(vec.try_map_alloc(|()| 0u64).is_err());
+}
+
+#[test]
+fn split_map() {
+    let vec = (0u32..10).collect::<Vec<_>>();
+    let ptr = vec.as_ptr() as usize;
+
+    let (small, large) = vec.split_map(|&x| x < 3, |x| x as i32 - 3);
+    assert_eq!(small, [-3, -2, -1]);
+    assert_eq!(large, [0, 1, 2, 3, 4, 5, 6]);
+    assert_eq!(small.len() + large.len(), 10);
+    assert_reuse!(small.as_ptr() as usize, ptr);
+}
+
+#[test]
+fn zip_with_default() {
+    // left longer
+    let mut a = Vec::with_capacity(8);
+    a.extend(0u32..5);
+    let ptr = a.as_ptr() as usize;
+    let vec = a.zip_with_default(vec![10u32, 20], |x, y| x + y);
+    assert_eq!(vec, [10, 21, 2, 3, 4]);
+    assert_reuse!(vec.as_ptr() as usize, ptr);
+
+    // right longer, and the reused left vector has to grow
+    let vec = vec![1u32].zip_with_default(vec![1u64, 2, 3], |x, y| x + y as u32);
+    assert_eq!(vec, [2, 2, 3]);
+    assert!(vec.capacity() >= 3);
+
+    let vec = Vec::<u32>::new().zip_with_default(vec![String::from("a")], |x, y| (x, y));
+    assert_eq!(vec, [(0, String::from("a"))]);
+}
+
+#[test]
+fn zip_longest_with() {
+    fn show(x: EitherOrBoth<u32, char>) -> String {
+        match x {
+            EitherOrBoth::Both(x, y) => format!("{}{}", x, y),
+            EitherOrBoth::Left(x) => x.to_string(),
+            EitherOrBoth::Right(y) => y.to_string(),
+        }
+    }
+
+    let vec = vec![1u32, 2, 3].zip_longest_with(vec!['a'], show);
+    assert_eq!(vec, ["1a", "2", "3"]);
+
+    let vec = vec![1u32].zip_longest_with(vec!['a', 'b', 'c'], show);
+    assert_eq!(vec, ["1a", "b", "c"]);
+
+    let vec = vec![1u32, 2].zip_longest_with(vec!['a', 'b'], show);
+    assert_eq!(vec, ["1a", "2b"]);
+
+    let mut a = Vec::with_capacity(10);
+    a.extend(0u32..5);
+    let ptr = a.as_ptr() as usize;
+    let vec = a.zip_longest_with(vec![1u32, 1], |x| match x {
+        EitherOrBoth::Both(x, y) => x + y,
+        EitherOrBoth::Left(x) | EitherOrBoth::Right(x) => x,
+    });
+    assert_eq!(vec, [1, 2, 2, 3, 4]);
+    assert_reuse!(vec.as_ptr() as usize, ptr);
+}
+
+#[test]
+fn interleave() {
+    let vec = vec![1, 3, 5].interleave(vec![2, 4, 6, 8]);
+    assert_eq!(vec, [1, 2, 3, 4, 5, 6, 8]);
+
+    let vec = vec![1, 3, 5, 7, 9].interleave(vec![2, 4]);
+    assert_eq!(vec, [1, 2, 3, 4, 5, 7, 9]);
+
+    let vec = Vec::new().interleave(vec![1, 2]);
+    assert_eq!(vec, [1, 2]);
+
+    // the larger allocation is reused when it can fit both vectors
+    let mut b = Vec::with_capacity(10);
+    b.extend([2, 4, 6]);
+    let ptr = b.as_ptr() as usize;
+    let vec = vec![1, 3, 5].interleave(b);
+    assert_eq!(vec, [1, 2, 3, 4, 5, 6]);
+    assert_reuse!(vec.as_ptr() as usize, ptr);
+}
+
+#[test]
+fn consume_fold() {
+    let vec = vec![1u32, 2, 3, 4];
+    assert_eq!(vec.consume_fold(0, |acc, x| acc + x), 10);
+
+    let vec = vec![String::from("a"), String::from("b")];
+    assert_eq!(vec.consume_fold(String::new(), |acc, x| acc + &x), "ab");
+
+    assert_eq!(Vec::<u32>::new().consume_fold(5, |acc, x| acc + x), 5);
+}
+
+#[test]
+fn try_reduce() {
+    let vec = vec![1u32, 2, 3, 4];
+    assert_eq!(
+        vec.try_reduce(|x, y| x.checked_add(y).ok_or(())),
+        Ok(Some(10))
+    );
+
+    let vec = vec![u32::MAX, 1, 2];
+    assert_eq!(vec.try_reduce(|x, y| x.checked_add(y).ok_or(())), Err(()));
+
+    let vec = Vec::<u32>::new();
+    assert_eq!(vec.try_reduce(|x, y| Ok::<_, ()>(x + y)), Ok(None));
+
+    let vec = vec![7u32];
+    assert_eq!(vec.try_reduce(|x, y| Ok::<_, ()>(x + y)), Ok(Some(7)));
+}
+
+#[test]
+fn zip_with_array() {
+    let vec = vec![1u32, 2, 3, 4];
+    let ptr = vec.as_ptr() as usize;
+    let vec = vec.zip_with_array([10u32, 20], |x, y| x + y);
+    assert_eq!(vec, [11, 22]);
+    assert_reuse!(vec.as_ptr() as usize, ptr);
+
+    let vec = vec![1u32, 2];
+    let vec = vec.zip_with_array([1.5f32, 2.5, 3.5, 4.5], |x, y| x as f32 * y);
+    assert_eq!(vec, [1.5, 5.0]);
+}
+
+#[test]
+fn map_rev() {
+    let vec = vec![1u32, 2, 3, 4];
+    let ptr = vec.as_ptr() as usize;
+    let mut order = Vec::new();
+    let vec = vec.map_rev(|x| {
+        order.push(x);
+        x as f32 * 2.0
+    });
+    assert_eq!(vec, [2.0, 4.0, 6.0, 8.0]);
+    assert_eq!(order, [4, 3, 2, 1]);
+    assert_reuse!(vec.as_ptr() as usize, ptr);
+
+    let mut order = Vec::new();
+    let vec = vec![1u8, 2, 3].map_rev(|x| {
+        order.push(x);
+        u64::from(x)
+    });
+    assert_eq!(vec, [1, 2, 3]);
+    assert_eq!(order, [3, 2, 1]);
+}
+
+#[test]
+fn zip_with_mut() {
+    let mut vec = vec![1u32, 2, 3];
+    vec.zip_with_mut(vec![10u64, 20, 30, 40], |x, y| *x += y as u32);
+    assert_eq!(vec, [11, 22, 33]);
+
+    vec.zip_with_mut(vec![1.0f32], |x, y| *x *= y as u32 * 2);
+    assert_eq!(vec, [22]);
+}
+
+#[test]
+fn map_with_len() {
+    let vec = vec![1u64, 1, 1, 1];
+    let ptr = vec.as_ptr() as usize;
+    let vec = vec.map_with_len(|i, len, x| (x + i as u64) as f64 / len as f64);
+    assert_eq!(vec, [0.25, 0.5, 0.75, 1.0]);
+    assert_reuse!(vec.as_ptr() as usize, ptr);
+}
+
+#[test]
+fn map_chunks_exact() {
+    let vec = (0u32..10).collect::<Vec<_>>();
+    let mut sums = Vec::new();
+
+    vec.map_chunks_exact::<4, _>(|chunk| {
+        chunk.iter_mut().for_each(|x| *x *= 2);
+        sums.push(chunk.iter().sum::<u32>());
+    });
+
+    assert_eq!(sums, [12, 44, 34]);
+}
+
+#[test]
+#[should_panic(expected = "Tried to map chunks of size zero")]
+fn map_chunks_exact_zero() {
+    vec![1u32].map_chunks_exact::<0, _>(|_| ());
+}
+
+#[test]
+fn map_with_capacity() {
+    let vec = (0u32..100).collect::<Vec<_>>();
+    let vec = vec.map_with_capacity(u128::from);
+    assert_eq!(vec, (0..100).collect::<Vec<_>>());
+    assert_eq!(vec.capacity(), 100);
+
+    let vec = vec![1u32, 2, 3];
+    let ptr = vec.as_ptr() as usize;
+    let vec = vec.map_with_capacity(|x| x as f32);
+    assert_eq!(vec, [1.0, 2.0, 3.0]);
+    assert_reuse!(vec.as_ptr() as usize, ptr);
+}
+
+#[test]
+fn map_unchecked() {
+    let vec = vec![1u32, 2, 3];
+    let ptr = vec.as_ptr() as usize;
+    let vec = unsafe { vec.map_unchecked(|x| x as f32) };
+    assert_eq!(vec, [1.0, 2.0, 3.0]);
+    assert_reuse!(vec.as_ptr() as usize, ptr);
+
+    let vec = unsafe { Vec::<u32>::new().map_unchecked(|x| x as i32) };
+    assert!(vec.is_empty());
+}
+
+#[test]
+fn map_reporting() {
+    let (vec, reused) = vec![1u32, 2, 3].map_reporting(|x| x as f32);
+    assert_eq!(vec, [1.0, 2.0, 3.0]);
+    assert_reuse!(reused, Reused::Buffer);
+
+    let (vec, reused) = vec![1u8, 2, 3].map_reporting(u64::from);
+    assert_eq!(vec, [1, 2, 3]);
+    assert_eq!(reused, Reused::Fresh);
+
+    let (vec, reused) = Vec::<u32>::new().map_reporting(|x| x as i32);
+    assert!(vec.is_empty());
+    assert_eq!(reused, Reused::Fresh);
+}
+
+#[test]
+fn map_pooled() {
+    let mut pool = VecPool::new();
+
+    let vec = vec![1u8, 2, 3].map_pooled(&mut pool, u64::from);
+    assert_eq!(vec, [1, 2, 3]);
+    let ptr = vec.as_ptr() as usize;
+
+    pool.recycle(vec);
+    assert_eq!(pool.len(), 1);
+
+    // the recycled allocation is used for the next output
+    let vec = vec![4u8, 5].map_pooled(&mut pool, u64::from);
+    assert_eq!(vec, [4, 5]);
+    assert_reuse!(vec.as_ptr() as usize, ptr);
+    assert!(pool.is_empty());
+
+    // the pool isn't used if the input allocation can be reused
+    pool.recycle(vec);
+    let vec = vec![1u32, 2, 3];
+    let ptr = vec.as_ptr() as usize;
+    let vec = vec.map_pooled(&mut VecPool::new(), |x| x as f32);
+    assert_eq!(vec, [1.0, 2.0, 3.0]);
+    assert_reuse!(vec.as_ptr() as usize, ptr);
+    assert_eq!(pool.len(), 1);
+
+    // buffers with a compatible layout can be recycled into the pool
+    pool.recycle(vec![1i64, 2]);
+    assert_reuse!(pool.len(), 2);
+    assert_eq!(pool.take(), []);
+}
+
+#[test]
+fn map_collect_errors() {
+    let vec = vec![1u32, 2, 3, 4, 5, 6];
+    let ptr = vec.as_ptr() as usize;
+    let vec = vec.map_collect_errors(|x| Ok::<_, u32>(x as i32 * 2));
+    let vec = vec.unwrap();
+    assert_eq!(vec, [2, 4, 6, 8, 10, 12]);
+    assert_reuse!(vec.as_ptr() as usize, ptr);
+
+    let res = vec.map_collect_errors(|x| if x % 4 == 0 { Err(x) } else { Ok(x as u32) });
+    assert_eq!(res, Err(vec![4, 8, 12]));
+
+    let res = Vec::<u8>::new().map_collect_errors(Err::<u8, u8>);
+    assert_eq!(res, Ok(vec![]));
+}
+
+#[test]
+fn map_rc() {
+    use std::rc::Rc;
+
+    let rc: Rc<[u32]> = Rc::from(vec![1, 2, 3]);
+    let ptr = rc.as_ptr() as usize;
+
+    let res = try_map_rc(rc, |x| x as f32);
+    assert_reuse!(res.is_ok(), true);
+
+    if let Ok(rc) = res {
+        assert_eq!(*rc, [1.0, 2.0, 3.0]);
+        assert_eq!(rc.as_ptr() as usize, ptr);
+    }
+
+    // shared `Rc`s and `Rc`s with `Weak`s can't be mapped in place
+    let rc: Rc<[u32]> = Rc::from(vec![1, 2, 3]);
+    let other = rc.clone();
+    let rc = try_map_rc(rc, |x| x as f32).unwrap_err();
+    drop(other);
+
+    let weak = Rc::downgrade(&rc);
+    let rc = try_map_rc(rc, |x| x as f32).unwrap_err();
+    drop(weak);
+
+    // neither can `Rc`s of a different layout
+    let rc = try_map_rc(rc, u64::from).unwrap_err();
+    assert_eq!(*rc, [1, 2, 3]);
+}
+
+#[test]
+fn map_arc() {
+    use std::sync::Arc;
+
+    let arc: Arc<[u32]> = Arc::from(vec![1, 2, 3]);
+    let ptr = arc.as_ptr() as usize;
+
+    let res = try_map_arc(arc, |x| x as f32);
+    assert_reuse!(res.is_ok(), true);
+
+    if let Ok(arc) = res {
+        assert_eq!(*arc, [1.0, 2.0, 3.0]);
+        assert_eq!(arc.as_ptr() as usize, ptr);
+    }
+
+    let arc: Arc<[u32]> = Arc::from(vec![1, 2, 3]);
+    let other = arc.clone();
+    let arc = try_map_arc(arc, |x| x as f32).unwrap_err();
+    drop(other);
+
+    let weak = Arc::downgrade(&arc);
+    let arc = try_map_arc(arc, |x| x as f32).unwrap_err();
+    drop(weak);
+
+    let arc = try_map_arc(arc, u64::from).unwrap_err();
+    assert_eq!(*arc, [1, 2, 3]);
+}
+
+#[test]
+fn map_split2() {
+    let vec = vec![1u64, 2, 3, 4];
+    let ptr = vec.as_ptr() as usize;
+
+    // the `B`s reuse the allocation, and the `A`s get an exact allocation
+    let (a, b) = vec.map_split2(|x| (x as u8, x as f64 * 0.5));
+    assert_eq!(a, [1, 2, 3, 4]);
+    assert_eq!(a.capacity(), 4);
+    assert_eq!(b, [0.5, 1.0, 1.5, 2.0]);
+    assert_reuse!(b.as_ptr() as usize, ptr);
+
+    // neither output can reuse the allocation
+    let mut vec = Vec::with_capacity(10);
+    vec.extend([1u16, 2, 3]);
+    let (a, b) = vec.map_split2(|x| (u32::from(x), u64::from(x)));
+    assert_eq!((a.capacity(), b.capacity()), (3, 3));
+    assert_eq!((a, b), (vec![1, 2, 3], vec![1, 2, 3]));
+}
+
+#[test]
+fn replace_map() {
+    let mut vec = vec![1u32, 2, 3];
+    let ptr = vec.as_ptr() as usize;
+
+    {
+        let mut floats = vec.replace_map(|x| x as f32);
+        assert_eq!(**floats, [1.0, 2.0, 3.0]);
+        assert_reuse!(floats.as_ptr() as usize, ptr);
+        floats.retain(|&x| x > 1.0);
+    }
+
+    // the allocation is given back to `vec`
+    assert!(vec.is_empty());
+    assert!(vec.capacity() >= 3);
+    assert_reuse!(vec.as_ptr() as usize, ptr);
+
+    vec.extend([4, 5]);
+    let floats = vec.replace_map(|x| x as f32).into_inner();
+    assert_eq!(floats, [4.0, 5.0]);
+    assert_eq!(vec.capacity(), 0);
+}
+
+#[test]
+#[should_panic = "Tried to replace_map into a type with a different layout"]
+fn replace_map_different_layout() {
+    let mut vec = vec![1u32, 2, 3];
+    vec.replace_map(u64::from);
+}
+
+#[test]
+fn zip_with_until() {
+    let a = vec![1u32, 2, 3, 4, 5];
+    let b = vec![10u32, 20, 30, 40];
+    let ptr = a.as_ptr() as usize;
+
+    let vec = a.zip_with_until(b, |x, y| (x + y, x + y > 30));
+    assert_eq!(vec, [11, 22, 33]);
+    assert_reuse!(vec.as_ptr() as usize, ptr);
+
+    // without a stop, this is the same as `zip_with`
+    let vec = vec![1u8, 2, 3].zip_with_until(vec![1.0f32, 2.0], |x, y| (f32::from(x) * y, false));
+    assert_eq!(vec, [1.0, 4.0]);
+
+    // the right allocation is reused if it's the only one with a matching layout
+    let right = vec![1.0f32, 2.0, 3.0];
+    let ptr = right.as_ptr() as usize;
+    let vec = vec![1u8, 2, 3].zip_with_until(right, |x, y| (f32::from(x) + y, x == 2));
+    assert_eq!(vec, [2.0, 4.0]);
+    assert_reuse!(vec.as_ptr() as usize, ptr);
+}
+
+#[test]
+fn group_map() {
+    let vec = vec![1u32, 1, 2, 3, 3, 3, 1];
+    let ptr = vec.as_ptr() as usize;
+
+    let vec = vec.group_map(|&x| x, |run| run.iter().sum::<u32>() as f32);
+    assert_eq!(vec, [2.0, 2.0, 9.0, 1.0]);
+    assert_reuse!(vec.as_ptr() as usize, ptr);
+
+    let vec = (0u8..10).collect::<Vec<_>>();
+    let vec = vec.group_map(|x| x / 4, |run| run.len());
+    assert_eq!(vec, [4, 4, 2]);
+
+    let vec =
+        Vec::<u32>::new().group_map(|_| -> u32 { unreachable!() }, |_| -> u32 { unreachable!() });
+    assert!(vec.is_empty());
+}
+
+#[test]
+fn map_larger_alignment() {
+    let vec = vec![[1u8, 0, 0, 0], [0, 1, 0, 0], [0, 0, 0, 1]];
+    let ptr = vec.as_ptr() as usize;
+
+    // the input is only aligned for `u8`, so this allocates a new
+    // vector, even if the input happens to be aligned for `u32`
+    let vec = vec.map(u32::from_le_bytes);
+    assert_eq!(vec, [1, 1 << 8, 1 << 24]);
+    assert_ne!(vec.as_ptr() as usize, ptr);
+}
+
+#[test]
+fn map_with_progress() {
+    let vec = (0u32..10).collect::<Vec<_>>();
+    let ptr = vec.as_ptr() as usize;
+    let mut reports = Vec::new();
+
+    let vec = vec.map_with_progress(3, |len| reports.push(len), |x| x as f32);
+    assert_eq!(vec, (0..10).map(|x| x as f32).collect::<Vec<_>>());
+    assert_eq!(reports, [3, 6, 9]);
+    assert_reuse!(vec.as_ptr() as usize, ptr);
+
+    let mut reports = Vec::new();
+    let vec = vec![1u8; 4].map_with_progress(1, |len| reports.push(len), |x| x);
+    assert_eq!(vec, [1; 4]);
+    assert_eq!(reports, [1, 2, 3, 4]);
+}
+
+#[test]
+#[should_panic = "Tried to report progress every zero elements"]
+fn map_with_progress_zero() {
+    vec![0u8].map_with_progress(0, |_| (), |x| x);
+}
+
+#[test]
+fn vec_zip() {
+    let a = vec![1u8, 2, 3, 4, 5];
+    let b = vec![10u32, 20, 30];
+    let c = Vec::with_capacity(8);
+    let d = vec![1u64, 1, 1, 1];
+
+    let mut c = c;
+    c.extend_from_slice(&[0.5f32, 1.5, 2.5, 3.5]);
+    let ptr = c.as_ptr() as usize;
+
+    // `c` has the most capacity out of the vectors with the same layout as the output
+    let vec = (a, b, c, d).zip_with(|(a, b, c, d)| u32::from(a) + b + c as u32 + d as u32);
+    assert_eq!(vec, [12, 24, 36]);
+    assert_reuse!(vec.as_ptr() as usize, ptr);
+
+    let vec = (vec![1u8, 2],).zip_with(|(x,)| x * 2);
+    assert_eq!(vec, [2, 4]);
+}
+
+#[test]
+fn try_map_flatten() {
+    let vec = vec![1u32, 2, 3];
+    let ptr = vec.as_ptr() as usize;
+
+    let vec = vec.try_map_flatten(|x| Ok::<_, u32>(Ok(x as f32))).unwrap();
+    assert_eq!(vec, [1.0, 2.0, 3.0]);
+    assert_reuse!(vec.as_ptr() as usize, ptr);
+
+    let outer = vec![1u32, 2, 3].try_map_flatten(|x| if x == 2 { Err(x) } else { Ok(Ok(x)) });
+    assert_eq!(outer, Err(2));
+
+    let inner = vec![1u32, 2, 3].try_map_flatten(|x| Ok(if x == 3 { Err(x) } else { Ok(x) }));
+    assert_eq!(inner, Err(3));
+}
+
+#[test]
+fn map_track_count() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    let counter = AtomicUsize::new(usize::MAX);
+    let vec = vec![1u32, 2, 3];
+    let ptr = vec.as_ptr() as usize;
+
+    let vec = vec.map_track_count(&counter, |x| x as f32);
+    assert_eq!(vec, [1.0, 2.0, 3.0]);
+    assert_eq!(counter.load(Ordering::Relaxed), 3);
+    assert_reuse!(vec.as_ptr() as usize, ptr);
+
+    let res = std::panic::catch_unwind(|| {
+        vec![1u32, 2, 3, 4].map_track_count(&counter, |x| {
+            if x == 3 {
+                panic!("stop");
+            }
+
+            x
+        })
+    });
+
+    assert!(res.is_err());
+    assert_eq!(counter.load(Ordering::Relaxed), 2);
+}
+
+#[test]
+fn zip_into_map() {
+    let keys = vec!["a", "b", "a", "c"];
+    let values = vec![1, 2, 3, 4, 5];
+
+    let map = keys.clone().zip_into_map(values.clone());
+    assert_eq!(map.len(), 3);
+    assert_eq!(map["a"], 3);
+    assert_eq!(map["b"], 2);
+    assert_eq!(map["c"], 4);
+
+    let map = keys.zip_into_map_with(values, |earlier, later| earlier + later);
+    assert_eq!(map.len(), 3);
+    assert_eq!(map["a"], 4);
+    assert_eq!(map["b"], 2);
+    assert_eq!(map["c"], 4);
+
+    let map = Vec::<u8>::new().zip_into_map(vec![1, 2]);
+    assert!(map.is_empty());
+}
+
+#[test]
+fn clear_and_reuse() {
+    let vec = vec![1u32, 2, 3];
+    let ptr = vec.as_ptr() as usize;
+    let cap = vec.capacity();
+
+    let vec = vec.clear_and_reuse::<f32>();
+    assert!(vec.is_empty());
+    assert_reuse!(vec.as_ptr() as usize, ptr);
+    assert_reuse!(vec.capacity(), cap);
+
+    // the alignments match, but the sizes don't
+    let vec = vec![1u32, 2, 3].clear_and_reuse::<[u32; 2]>();
+    assert!(vec.is_empty());
+    assert_eq!(vec.capacity(), 0);
+
+    let vec = vec![(), ()].clear_and_reuse::<()>();
+    assert!(vec.is_empty());
+}
+
+#[test]
+fn flatten() {
+    use std::num::NonZeroU32;
+
+    let vec = (0u32..10).map(NonZeroU32::new).collect::<Vec<_>>();
+    let ptr = vec.as_ptr() as usize;
+    let cap = vec.capacity();
+
+    let vec = flatten_options(vec);
+    assert_eq!(
+        vec,
+        (1..10)
+            .map(|x| NonZeroU32::new(x).unwrap())
+            .collect::<Vec<_>>()
+    );
+    assert_reuse!(vec.as_ptr() as usize, ptr);
+    assert_reuse!(vec.capacity(), cap);
+
+    let vec = flatten_options(vec![None, Some(1u32), None, Some(2)]);
+    assert_eq!(vec, [1, 2]);
+}
+
+#[test]
+fn take_map() {
+    let mut vec = vec![1u32, 2, 3];
+    let ptr = vec.as_ptr() as usize;
+
+    let out = vec.take_map(|x| x as f32);
+    assert_eq!(out, [1.0, 2.0, 3.0]);
+    assert_reuse!(out.as_ptr() as usize, ptr);
+    assert!(vec.is_empty());
+    assert_eq!(vec.capacity(), 0);
+
+    let mut vec = vec![1u32, 2, 3];
+    let out = vec.take_map(|x| x as u8);
+    assert_eq!(out, [1, 2, 3]);
+    assert_eq!(vec.capacity(), 0);
+}
+
+#[test]
+fn zip_with_compatible_layout() {
+    // `u32` is smaller than `[u32; 2]`, so the output is written behind the unread inputs
+    let a = vec![[1u32, 1], [2, 2], [3, 3], [4, 4]];
+    let b = vec![[10u32, 10], [20, 20], [30, 30]];
+    let ptr = a.as_ptr() as usize;
+
+    let vec = a.zip_with(b, |x, y| x[0] + y[1]);
+    assert_eq!(vec, [11, 22, 33]);
+    assert_reuse!(vec.as_ptr() as usize, ptr);
+
+    // `a` has the larger allocation in bytes
+    let a = vec![[1u32; 4], [2; 4], [3; 4]];
+    let b = vec![10u32, 20, 30, 40];
+    let ptr = a.as_ptr() as usize;
+
+    let vec = a.zip_with(b, |x, y| [x[0], y]);
+    assert_eq!(vec, [[1, 10], [2, 20], [3, 30]]);
+    assert_reuse!(vec.as_ptr() as usize, ptr);
+
+    // `[u32; 2]` is larger than `u32`, but `b` has enough spare capacity,
+    // and it has the larger allocation in bytes
+    let a = vec![[1u32; 4], [2; 4], [3; 4]];
+    let mut b = Vec::with_capacity(20);
+    b.extend_from_slice(&[10u32, 20, 30, 40]);
+    let ptr = b.as_ptr() as usize;
+
+    let vec = a.zip_with(b, |x, y| [x[0], y]);
+    assert_eq!(vec, [[1, 10], [2, 20], [3, 30]]);
+    assert_reuse!(vec.as_ptr() as usize, ptr);
+
+    // the alignments don't match, so this can't be reused
+    let vec = vec![1u64, 2].zip_with(vec![1u64, 2], |x, y| (x + y) as u32);
+    assert_eq!(vec, [2, 4]);
+}
+
+#[test]
+fn zip_with_grow() {
+    // `[u32; 3]` is larger than `u32`, and neither input has any spare capacity,
+    // so `a` has the larger allocation, and is grown to hold all 4 of it's elements
+    let a = vec![1u32, 2, 3, 4];
+    let b = vec![10u32, 20, 30];
+
+    let vec = a.zip_with(b, |x, y| [x, y, x + y]);
+    assert_eq!(vec, [[1, 10, 11], [2, 20, 22], [3, 30, 33]]);
+    assert_reuse!(vec.capacity(), 4);
+
+    // `b` has the larger allocation
+    let a = vec![1u32, 2];
+    let b = vec![10u32, 20, 30];
+
+    let vec = a.zip_with(b, |x, y| [x, y]);
+    assert_eq!(vec, [[1, 10], [2, 20]]);
+    assert_reuse!(vec.capacity(), 3);
+
+    // the alignments don't match, so neither input can be grown
+    let vec = vec![1u8, 2].zip_with(vec![3u8, 4], |x, y| u32::from(x * y));
+    assert_eq!(vec, [3, 8]);
+}
+
+#[test]
+fn map_send() {
+    use std::sync::mpsc::sync_channel;
+
+    let (tx, rx) = sync_channel(2);
+
+    let handle = std::thread::spawn(move || vec![1u32, 2, 3, 4].map_send(tx, |x| x * 2));
+
+    assert_eq!(rx.iter().collect::<Vec<_>>(), [2, 4, 6, 8]);
+    assert!(handle.join().unwrap().is_ok());
+
+    let (tx, rx) = sync_channel(4);
+    drop(rx);
+
+    let err = vec![1u32, 2, 3].map_send(tx, |x| x * 2).unwrap_err();
+    assert_eq!(err.0, 2);
+}
+
+#[test]
+fn wrap() {
+    use std::num::Wrapping;
+
+    #[derive(Debug, PartialEq)]
+    #[repr(transparent)]
+    struct Meters(f64);
+
+    unsafe impl TransparentWrapper<f64> for Meters {}
+
+    let vec = vec![1.0, 2.5, 4.0];
+    let ptr = vec.as_ptr() as usize;
+
+    let vec = vec.wrap::<Meters>();
+    assert_eq!(vec, [Meters(1.0), Meters(2.5), Meters(4.0)]);
+    assert_reuse!(vec.as_ptr() as usize, ptr);
+
+    let vec = vec.unwrap_newtype::<f64>();
+    assert_eq!(vec, [1.0, 2.5, 4.0]);
+    assert_reuse!(vec.as_ptr() as usize, ptr);
+
+    let vec = vec![u8::MAX, 1]
+        .wrap::<Wrapping<u8>>()
+        .map(|x| x + Wrapping(1));
+    assert_eq!(vec.unwrap_newtype::<u8>(), [0, 2]);
+}
+
+#[test]
+fn map_shrink() {
+    // `[u32; 2]` is larger than `u32`, so this is written behind the unread inputs
+    let mut vec = Vec::with_capacity(100);
+    vec.extend_from_slice(&[[1u32, 2], [3, 4], [5, 6]]);
+
+    let vec = vec.map_shrink(|[x, y]| x + y);
+    assert_eq!(vec, [3, 7, 11]);
+    assert_eq!(vec.capacity(), vec.len());
+
+    let mut vec = Vec::with_capacity(100);
+    vec.extend_from_slice(&[1u64, 2, 3]);
+
+    let vec = vec.map_shrink(|x| x as u8);
+    assert_eq!(vec, [1, 2, 3]);
+    assert_eq!(vec.capacity(), vec.len());
+
+    // the capacity already matches the length, so the allocation is reused as is
+    let vec = vec![1u32, 2, 3];
+    let ptr = vec.as_ptr() as usize;
+
+    let vec = vec.map_shrink(|x| x as f32);
+    assert_eq!(vec, [1.0, 2.0, 3.0]);
+    assert_eq!(vec.capacity(), vec.len());
+    assert_reuse!(vec.as_ptr() as usize, ptr);
+}
+
+#[test]
+fn zip_with_tagged() {
+    let a = vec![1u32, 2, 3];
+    let b = vec![4u32, 5];
+    let ptr = a.as_ptr() as usize;
+
+    let vec = a.zip_with_tagged(7, b, 7, |a, b| a * b);
+    assert_eq!(vec, [4, 10]);
+    assert_reuse!(vec.as_ptr() as usize, ptr);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic = "Tried to zip vectors with different tags"]
+fn zip_with_tagged_mismatch() {
+    vec![1u32].zip_with_tagged(1, vec![2u32], 2, |a, b| a * b);
+}
+
+#[test]
+fn map_arrays() {
+    let vec = vec![[1.0f32, 2.0, 3.0, 4.0], [0.5, 0.25, -1.0, 8.0]];
+    let expected = vec.iter().map(|x| x.map(f32::to_bits)).collect::<Vec<_>>();
+    let ptr = vec.as_ptr() as usize;
+
+    let vec = vec.map_arrays(|x| x.map(f32::to_bits));
+    assert_eq!(vec, expected);
+    assert_reuse!(vec.as_ptr() as usize, ptr);
+
+    let vec = vec.map_arrays(|[a, b, c, d]| [d as u8, c as u8, b as u8, a as u8]);
+    assert_eq!(vec.len(), 2);
+}
+
+#[test]
+fn map_expanding() {
+    // each value is repeated as many times as it's value
+    let vec = vec![1u32, 0, 2, 1];
+    let ptr = vec.as_ptr() as usize;
+
+    let vec = vec.map_expanding(|x, out| out.extend((0..x).map(|_| x as f32)));
+    assert_eq!(vec, [1.0, 2.0, 2.0, 1.0]);
+    assert_reuse!(vec.as_ptr() as usize, ptr);
+
+    // the output catches up to the unread input
+    let vec = vec![1u32, 3, 0, 2];
+    let vec = vec.map_expanding(|x, out| {
+        assert!(out.is_empty());
+        out.extend((0..x).map(|_| x));
+    });
+    assert_eq!(vec, [1, 3, 3, 3, 2, 2]);
+
+    // the layouts don't match
+    let vec = vec![2u8, 0, 1].map_expanding(|x, out| out.extend((0..x).map(|_| u32::from(x))));
+    assert_eq!(vec, [2, 2, 1]);
+}
+
+#[test]
+fn map_two_phase() {
+    // each value is repeated as many times as it's value
+    let vec = vec![1u32, 3, 0, 2];
+    let vec = vec.map_two_phase(
+        |&x| x as usize,
+        |x, out| out.extend((0..x).map(|_| x as f32)),
+    );
+    assert_eq!(vec, [1.0, 3.0, 3.0, 3.0, 2.0, 2.0]);
+    assert_eq!(vec.capacity(), vec.len());
+
+    // the layouts don't match, so the output is allocated with exactly the counted length
+    let vec = vec![2u8, 0, 1, 4];
+    let vec = vec.map_two_phase(
+        |&x| usize::from(x),
+        |x, out| out.extend((0..x).map(|_| u32::from(x))),
+    );
+    assert_eq!(vec, [2, 2, 1, 4, 4, 4, 4]);
+    assert_eq!(vec.capacity(), 7);
+}
+
+#[test]
+fn map_two_phase_front_loaded() {
+    // the first value expands the most, so the output gets ahead of the input that was read
+    let mut vec = Vec::with_capacity(4);
+    vec.extend([2u32, 0, 1]);
+    let ptr = vec.as_ptr() as usize;
+
+    let vec = vec.map_two_phase(
+        |&x| x as usize,
+        |x, out| out.extend((0..x).map(|_| x as f32)),
+    );
+    assert_eq!(vec, [2.0, 2.0, 1.0]);
+    assert_reuse!(vec.as_ptr() as usize, ptr);
+    assert_reuse!(vec.capacity(), 4);
+
+    // there isn't enough room in front of the input, so it's grown by how far the output gets ahead
+    let vec = vec![2u32, 0, 1];
+    let vec = vec.map_two_phase(
+        |&x| x as usize,
+        |x, out| out.extend((0..x).map(|_| x as f32)),
+    );
+    assert_eq!(vec, [2.0, 2.0, 1.0]);
+    assert_reuse!(vec.capacity(), 4);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "The counted length didn't match the number of values that were filled")]
+fn map_two_phase_wrong_count() {
+    let _ = vec![1u32, 2, 3].map_two_phase(|_| 1, |x, out: &mut Vec<u32>| out.extend(0..x));
+}
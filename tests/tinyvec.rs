@@ -0,0 +1,132 @@
+#![cfg(feature = "tinyvec")]
+
+use std::rc::Rc;
+
+use tinyvec::{array_vec, tiny_vec, ArrayVec, TinyVec};
+use vec_utils::{ArrayVecExt, TinyVecExt};
+
+#[macro_use]
+mod common;
+
+#[test]
+fn array_vec_map() {
+    let vec: ArrayVec<[u32; 4]> = array_vec![1, 2, 3];
+
+    let vec = vec.map(|x| x as f32);
+    assert_eq!(*vec, [1.0, 2.0, 3.0]);
+
+    let vec = vec.map(f64::from);
+    assert_eq!(*vec, [1.0, 2.0, 3.0]);
+    assert_eq!(vec.into_inner(), [1.0, 2.0, 3.0, 0.0]);
+}
+
+#[test]
+fn array_vec_try_map() {
+    let vec: ArrayVec<[u32; 4]> = array_vec![1, 2, 3];
+    assert_eq!(vec.try_map(|x| if x == 2 { Err(x) } else { Ok(x) }), Err(2));
+
+    // the default values past the length are never passed to `f`
+    let vec: ArrayVec<[u32; 4]> = array_vec![1, 2, 3];
+    let vec = vec.try_map(|x| 12u32.checked_div(x)).ok().unwrap();
+    assert_eq!(*vec, [12, 6, 4]);
+}
+
+#[test]
+fn array_vec_zip_with() {
+    let left: ArrayVec<[u32; 4]> = array_vec![1, 2, 3];
+    let right: ArrayVec<[f32; 4]> = array_vec![1.0, 2.0, 3.0, 4.0];
+
+    let vec = left.zip_with(right, |x, y| x as f32 * y);
+    assert_eq!(*vec, [1.0, 4.0, 9.0]);
+}
+
+#[test]
+fn array_vec_map_panic() {
+    let value = Rc::new(());
+    let vec: ArrayVec<[Option<Rc<()>>; 8]> = (0..5).map(|_| Some(value.clone())).collect();
+
+    let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        let mut count = 0;
+
+        vec.map(|x| {
+            count += 1;
+
+            if count == 3 {
+                panic!("stop");
+            }
+
+            x
+        })
+    }));
+
+    assert!(res.is_err());
+    assert_eq!(Rc::strong_count(&value), 1);
+}
+
+#[test]
+fn tiny_vec_map_inline() {
+    let vec: TinyVec<[u32; 4]> = tiny_vec![1, 2, 3];
+
+    let vec = vec.map(|x| x as f32);
+    assert!(vec.is_inline());
+    assert_eq!(*vec, [1.0, 2.0, 3.0]);
+
+    let vec = vec.map(f64::from);
+    assert!(vec.is_inline());
+    assert_eq!(*vec, [1.0, 2.0, 3.0]);
+}
+
+#[test]
+fn tiny_vec_map_heap() {
+    let vec: TinyVec<[u32; 2]> = tiny_vec![1, 2, 3, 4];
+    assert!(vec.is_heap());
+    let ptr = vec.as_ptr() as usize;
+
+    let vec = vec.map(|x| x as f32);
+    assert!(vec.is_heap());
+    assert_eq!(*vec, [1.0, 2.0, 3.0, 4.0]);
+    assert_reuse!(vec.as_ptr() as usize, ptr);
+
+    let vec = vec.map(f64::from);
+    assert!(vec.is_heap());
+    assert_eq!(*vec, [1.0, 2.0, 3.0, 4.0]);
+}
+
+#[test]
+fn tiny_vec_try_map() {
+    let vec: TinyVec<[u32; 2]> = tiny_vec![1, 2, 3];
+    assert_eq!(vec.try_map(|x| if x == 2 { Err(x) } else { Ok(x) }), Err(2));
+
+    let vec: TinyVec<[u32; 4]> = tiny_vec![1, 2, 3];
+    assert_eq!(vec.try_map(|x| if x == 2 { Err(x) } else { Ok(x) }), Err(2));
+
+    let vec: TinyVec<[u32; 4]> = tiny_vec![1, 2, 3];
+    let vec = vec.try_map(|x| x.checked_mul(2)).ok().unwrap();
+    assert_eq!(*vec, [2, 4, 6]);
+}
+
+#[test]
+fn tiny_vec_zip_with() {
+    let inline: TinyVec<[u32; 4]> = tiny_vec![1, 2, 3];
+    let heap: TinyVec<[u32; 4]> = tiny_vec![10, 20, 30, 40, 50];
+
+    let vec = inline.clone().zip_with(inline.clone(), |x, y| x + y);
+    assert!(vec.is_inline());
+    assert_eq!(*vec, [2, 4, 6]);
+
+    let heap_clone = heap.clone();
+    let ptr = heap_clone.as_ptr() as usize;
+    let vec = heap_clone.zip_with(inline.clone(), |x, y| x - y);
+    assert_eq!(*vec, [9, 18, 27]);
+    assert_reuse!(vec.as_ptr() as usize, ptr);
+
+    let vec = inline.zip_with(heap, |x, y| y - x);
+    assert_eq!(*vec, [9, 18, 27]);
+
+    let left: TinyVec<[u32; 2]> = tiny_vec![1, 2, 3];
+    let right: TinyVec<[f32; 2]> = tiny_vec![1.0, 2.0, 3.0, 4.0];
+    let ptr_right = right.as_ptr() as usize;
+    let vec = left.zip_with(right, |x, y| x as f32 * y);
+    assert_eq!(*vec, [1.0, 4.0, 9.0]);
+    assert_reuse!(vec.as_ptr() as usize, ptr_right);
+}